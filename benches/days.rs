@@ -0,0 +1,132 @@
+//! Per-day parse/part1/part2 timings, plus the known hot spots in isolation.
+//!
+//! Inputs come from the account named by `AOC_ACCOUNT`, or else the first
+//! account under `data/`; days without an input are skipped.
+
+use std::{collections::VecDeque, env, fs, path::Path};
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use advent_of_code_2020::{
+    answers::{self, Account},
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25, Solution,
+};
+
+// Days whose slowest part takes long enough that criterion's default 100
+// samples would take minutes.
+const SLOW: &[u32] = &[11, 15, 17, 19, 22, 23, 24, 25];
+
+fn account() -> Account {
+    let accounts = answers::discover(Path::new("data")).unwrap();
+    match env::var("AOC_ACCOUNT") {
+        Ok(name) => accounts.into_iter().find(|a| a.name == name).unwrap(),
+        Err(_) => accounts.into_iter().next().unwrap(),
+    }
+}
+
+fn input(account: &Account, day: u32) -> Option<String> {
+    let puzzle = account.puzzles.iter().find(|p| p.day == day)?;
+    Some(fs::read_to_string(&puzzle.input).unwrap())
+}
+
+fn bench_day<S: Solution>(c: &mut Criterion, account: &Account, day: u32) {
+    let input = match input(account, day) {
+        Some(input) => input,
+        None => return,
+    };
+    let mut group = c.benchmark_group(format!("day{:02}", day));
+    if SLOW.contains(&day) {
+        group.sample_size(10);
+    }
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+    let parsed = S::parse(&input).unwrap();
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&parsed))));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&parsed))));
+    group.finish();
+}
+
+fn days(c: &mut Criterion) {
+    let account = account();
+    bench_day::<day01::Day01>(c, &account, 1);
+    bench_day::<day02::Day02>(c, &account, 2);
+    bench_day::<day03::Day03>(c, &account, 3);
+    bench_day::<day04::Day04>(c, &account, 4);
+    bench_day::<day05::Day05>(c, &account, 5);
+    bench_day::<day06::Day06>(c, &account, 6);
+    bench_day::<day07::Day07>(c, &account, 7);
+    bench_day::<day08::Day08>(c, &account, 8);
+    bench_day::<day09::Day09>(c, &account, 9);
+    bench_day::<day10::Day10>(c, &account, 10);
+    bench_day::<day11::Day11>(c, &account, 11);
+    bench_day::<day12::Day12>(c, &account, 12);
+    bench_day::<day13::Day13>(c, &account, 13);
+    bench_day::<day14::Day14>(c, &account, 14);
+    bench_day::<day15::Day15>(c, &account, 15);
+    bench_day::<day16::Day16>(c, &account, 16);
+    bench_day::<day17::Day17>(c, &account, 17);
+    bench_day::<day18::Day18>(c, &account, 18);
+    bench_day::<day19::Day19>(c, &account, 19);
+    bench_day::<day20::Day20>(c, &account, 20);
+    bench_day::<day21::Day21>(c, &account, 21);
+    bench_day::<day22::Day22>(c, &account, 22);
+    bench_day::<day23::Day23>(c, &account, 23);
+    bench_day::<day24::Day24>(c, &account, 24);
+    bench_day::<day25::Day25>(c, &account, 25);
+}
+
+fn hot_spots(c: &mut Criterion) {
+    let account = account();
+    let mut group = c.benchmark_group("hot_spots");
+    group.sample_size(10);
+
+    if let Some(input) = input(&account, 15) {
+        let xs = day15::parse(&input).unwrap();
+        group.bench_function("day15_solve_30m", |b| {
+            b.iter(|| day15::solve(black_box(&xs), 30_000_000))
+        });
+    }
+
+    if let Some(input) = input(&account, 23) {
+        let mut cups = day23::parse(&input).unwrap();
+        cups.extend(cups.len() + 1..=1_000_000);
+        group.bench_function("day23_ring_step_10m", |b| {
+            b.iter_batched(
+                || day23::Ring::from(cups.as_ref()),
+                |mut ring| {
+                    for _ in 0..10_000_000 {
+                        ring.step();
+                    }
+                    ring
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    if let Some(input) = input(&account, 17) {
+        let slice = day17::parse_slice(&input).unwrap();
+        group.bench_function("day17_boot_6d_reduced", |b| {
+            b.iter(|| {
+                let universe = day17::Universe::<day17::P<6>>::from_slice(&slice).reduced();
+                day17::boot(universe, 6)
+            })
+        });
+    }
+
+    if let Some(input) = input(&account, 22) {
+        let (p1, p2) = day22::parse(&input).unwrap();
+        group.bench_function("day22_play_game", |b| {
+            b.iter_batched(
+                || (VecDeque::from(p1.clone()), VecDeque::from(p2.clone())),
+                |(mut p1, mut p2)| day22::play_game(&mut p1, &mut p2),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, days, hot_spots);
+criterion_main!(benches);
@@ -0,0 +1,245 @@
+//! Puzzle inputs and their expected answers, grouped by account.
+//!
+//! Each account is a directory `data/<account>/` holding `dayNN.input` files
+//! and an optional `answers.toml` manifest:
+//!
+//! ```toml
+//! [[puzzle]]
+//! day = 1
+//! part1 = "996075"
+//! part2 = "51810360"
+//! ```
+//!
+//! A puzzle's `input` defaults to `dayNN.input` and is relative to the
+//! manifest. A missing `part1`/`part2` is not checked.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    error::Error,
+    runner::{self, Part},
+};
+
+/// One puzzle input and the answers it should produce.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Puzzle {
+    pub day: u32,
+    #[serde(default)]
+    pub input: PathBuf,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// Every puzzle listed in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "puzzle", default)]
+    pub puzzles: Vec<Puzzle>,
+}
+
+/// The inputs and answers found in one account directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub name: String,
+    /// Sorted by day.
+    pub puzzles: Vec<Puzzle>,
+}
+
+/// A part whose answer differs from the expected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub day: u32,
+    pub part: u8,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {:02} part {}: expected {}, got {}",
+            self.day,
+            self.part,
+            self.expected,
+            self.actual.as_deref().unwrap_or("no answer")
+        )
+    }
+}
+
+impl Manifest {
+    /// Reads a manifest, resolving input paths against its directory.
+    pub fn load(path: &Path) -> Result<Manifest, Error> {
+        let raw = read(path)?;
+        let mut manifest: Manifest = toml::from_str(&raw).map_err(|err| Error::Manifest {
+            path: path.to_owned(),
+            message: err.to_string(),
+        })?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for puzzle in &mut manifest.puzzles {
+            if puzzle.input.as_os_str().is_empty() {
+                puzzle.input = PathBuf::from(input_name(puzzle.day));
+            }
+            puzzle.input = dir.join(&puzzle.input);
+        }
+        Ok(manifest)
+    }
+}
+
+impl Account {
+    /// Reads `dir/answers.toml` (if any) and adds every other `dayNN.input`
+    /// in `dir` as a puzzle with no expected answers.
+    pub fn load(dir: &Path) -> Result<Account, Error> {
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let manifest_path = dir.join("answers.toml");
+        let mut puzzles = if manifest_path.is_file() {
+            Manifest::load(&manifest_path)?.puzzles
+        } else {
+            Vec::new()
+        };
+        for path in read_dir(dir)? {
+            let day = match path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(input_day)
+            {
+                Some(day) => day,
+                None => continue,
+            };
+            if !puzzles.iter().any(|p| p.input == path) {
+                puzzles.push(Puzzle {
+                    day,
+                    input: path,
+                    part1: None,
+                    part2: None,
+                });
+            }
+        }
+        puzzles.sort_by_key(|p| p.day);
+        Ok(Account { name, puzzles })
+    }
+}
+
+/// Every account under `data`, i.e. each of its subdirectories, sorted by
+/// name.
+pub fn discover(data: &Path) -> Result<Vec<Account>, Error> {
+    read_dir(data)?
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .map(|path| Account::load(&path))
+        .collect()
+}
+
+impl Puzzle {
+    /// Solves the puzzle's input, returning every part that does not match.
+    pub fn check(&self) -> Result<Vec<Mismatch>, Error> {
+        let input = read(&self.input)?;
+        let report = runner::run(self.day, Part::Both, &input)?;
+        let mut mismatches = Vec::new();
+        for answer in report.answers {
+            let expected = match answer.part {
+                1 => &self.part1,
+                _ => &self.part2,
+            };
+            if let Some(expected) = expected {
+                if answer.value.as_ref() != Some(expected) {
+                    mismatches.push(Mismatch {
+                        day: self.day,
+                        part: answer.part,
+                        expected: expected.clone(),
+                        actual: answer.value,
+                    });
+                }
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| Error::Io {
+        path: path.to_owned(),
+        message: err.to_string(),
+    })
+}
+
+fn input_name(day: u32) -> String {
+    format!("day{:02}.input", day)
+}
+// The day of an input file named `dayNN.input`.
+fn input_day(name: &str) -> Option<u32> {
+    let day = name.strip_prefix("day")?.strip_suffix(".input")?;
+    match day.parse() {
+        Ok(n) if input_name(n) == name => Some(n),
+        _ => None,
+    }
+}
+
+// The entries of `dir`, sorted.
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let io_error = |err: std::io::Error| Error::Io {
+        path: dir.to_owned(),
+        message: err.to_string(),
+    };
+    let mut paths = fs::read_dir(dir)
+        .map_err(io_error)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error)?;
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::{input_day, Manifest, Puzzle};
+
+    #[test]
+    fn input_names() {
+        assert_eq!(input_day("day08.input"), Some(8));
+        assert_eq!(input_day("day25.input"), Some(25));
+        assert_eq!(input_day("day8.input"), None);
+        assert_eq!(input_day("answers.toml"), None);
+    }
+
+    #[test]
+    fn parser() {
+        let raw = r#"
+            [[puzzle]]
+            day = 1
+            input = "day01.input"
+            part1 = "996075"
+
+            [[puzzle]]
+            day = 25
+            input = "day25.input"
+        "#;
+        let manifest: Manifest = toml::from_str(raw).unwrap();
+        assert_eq!(
+            manifest.puzzles,
+            vec![
+                Puzzle {
+                    day: 1,
+                    input: PathBuf::from("day01.input"),
+                    part1: Some("996075".to_owned()),
+                    part2: None,
+                },
+                Puzzle {
+                    day: 25,
+                    input: PathBuf::from("day25.input"),
+                    part1: None,
+                    part2: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,603 @@
+//! Cellular automata: every generation, each cell's next state follows from
+//! its own state and how many of its neighbors are live.
+//!
+//! An [`Automaton`] pairs a [`Topology`], which says where cells are and
+//! which cells neighbor each other, with a [`Rule`].
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use crate::grid::{Grid, InfiniteGrid, Pos};
+
+/// How a cell's next state follows from its state and live-neighbor count.
+pub trait Rule<S> {
+    fn apply(&self, state: &S, live_neighbors: usize) -> S;
+}
+impl<S, F: Fn(&S, usize) -> S> Rule<S> for F {
+    fn apply(&self, state: &S, live_neighbors: usize) -> S {
+        self(state, live_neighbors)
+    }
+}
+
+/// A Life-like rule written `B<counts>/S<counts>`, e.g. `B3/S23`: a dead
+/// cell is born with any of the `B` numbers of live neighbors, and a live cell
+/// survives with any of the `S` numbers.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct LifeRule {
+    // Bit `n` is set if `n` live neighbors are enough.
+    birth: u128,
+    survival: u128,
+}
+impl LifeRule {
+    /// Panics if a count is 128 or more. Counts above 9 have no rule string.
+    pub fn new(birth: &[usize], survival: &[usize]) -> LifeRule {
+        let mask = |counts: &[usize]| counts.iter().fold(0, |mask, &n| mask | 1u128 << n);
+        LifeRule {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+}
+impl Rule<bool> for LifeRule {
+    fn apply(&self, &alive: &bool, live_neighbors: usize) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        live_neighbors < 128 && mask & 1 << live_neighbors != 0
+    }
+}
+impl FromStr for LifeRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid rule: {} (expected e.g. B3/S23)", s);
+        let counts = |part: &str, prefix: char| -> Result<Vec<usize>, String> {
+            let digits = part
+                .strip_prefix(prefix)
+                .or_else(|| part.strip_prefix(prefix.to_ascii_lowercase()))
+                .ok_or_else(invalid)?;
+            digits
+                .chars()
+                .map(|d| d.to_digit(10).map(|d| d as usize).ok_or_else(invalid))
+                .collect()
+        };
+        let (birth, survival) = s.trim().split_once('/').ok_or_else(invalid)?;
+        Ok(LifeRule::new(&counts(birth, 'B')?, &counts(survival, 'S')?))
+    }
+}
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u128| -> String {
+            (0..128)
+                .filter(|n| mask & 1 << n != 0)
+                .map(|n| n.to_string())
+                .collect()
+        };
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+/// The shape of an automaton's world.
+pub trait Topology {
+    /// One whole generation.
+    type World: PartialEq;
+    /// The state of a single cell.
+    type State;
+    /// The generation after `world`.
+    fn step<R: Rule<Self::State>>(&self, world: &Self::World, rule: &R) -> Self::World;
+}
+
+/// A bounded [`Grid`] of cells in any state. `neighbors` lists the cells
+/// that count as neighbors of a position, and `live` says which states count
+/// as live.
+pub struct Dense<S, N> {
+    neighbors: N,
+    live: fn(&S) -> bool,
+}
+impl<S, N> Dense<S, N>
+where
+    N: Fn(&Grid<S>, Pos) -> Vec<Pos>,
+{
+    pub fn new(neighbors: N, live: fn(&S) -> bool) -> Dense<S, N> {
+        Dense { neighbors, live }
+    }
+}
+impl<S, N> Topology for Dense<S, N>
+where
+    S: PartialEq,
+    N: Fn(&Grid<S>, Pos) -> Vec<Pos>,
+{
+    type World = Grid<S>;
+    type State = S;
+
+    fn step<R: Rule<S>>(&self, world: &Grid<S>, rule: &R) -> Grid<S> {
+        Grid::from_fn(world.width, world.height, |pos| {
+            let live = (self.neighbors)(world, pos)
+                .into_iter()
+                .filter(|&p| world.get(p).is_some_and(self.live))
+                .count();
+            rule.apply(world.get(pos).unwrap(), live)
+        })
+    }
+}
+
+/// An unbounded space of live or dead cells, stored as the set of live ones.
+/// Only live cells and their neighbors are visited, so a dead cell with no
+/// live neighbors always stays dead.
+pub struct Sparse<C, N> {
+    neighbors: N,
+    cell: PhantomData<C>,
+}
+impl<C, N> Sparse<C, N>
+where
+    N: Fn(&C) -> Vec<C>,
+{
+    pub fn new(neighbors: N) -> Sparse<C, N> {
+        Sparse {
+            neighbors,
+            cell: PhantomData,
+        }
+    }
+}
+impl<C, N> Topology for Sparse<C, N>
+where
+    C: Eq + Hash + Clone,
+    N: Fn(&C) -> Vec<C>,
+{
+    type World = HashSet<C>;
+    type State = bool;
+
+    fn step<R: Rule<bool>>(&self, world: &HashSet<C>, rule: &R) -> HashSet<C> {
+        let mut counts: HashMap<C, usize> = world.iter().map(|c| (c.clone(), 0)).collect();
+        for c in world {
+            for n in (self.neighbors)(c) {
+                *counts.entry(n).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .filter(|(c, live)| rule.apply(&world.contains(c), *live))
+            .map(|(c, _)| c)
+            .collect()
+    }
+}
+
+/// An unbounded [`InfiniteGrid`] of cells in any state, storing only the
+/// cells that are not in the default state. Only stored cells and neighbors
+/// of live ones are visited, so the default state must be one in which a cell
+/// with no live neighbors stays.
+pub struct Unbounded<S, N> {
+    neighbors: N,
+    live: fn(&S) -> bool,
+}
+impl<S, N> Unbounded<S, N>
+where
+    N: Fn(Pos) -> Vec<Pos>,
+{
+    pub fn new(neighbors: N, live: fn(&S) -> bool) -> Unbounded<S, N> {
+        Unbounded { neighbors, live }
+    }
+}
+impl<S, N> Topology for Unbounded<S, N>
+where
+    S: Default + PartialEq,
+    N: Fn(Pos) -> Vec<Pos>,
+{
+    type World = InfiniteGrid<S>;
+    type State = S;
+
+    fn step<R: Rule<S>>(&self, world: &InfiniteGrid<S>, rule: &R) -> InfiniteGrid<S> {
+        let mut counts: HashMap<Pos, usize> = world.positions().map(|pos| (pos, 0)).collect();
+        for (pos, state) in world.iter() {
+            if (self.live)(state) {
+                for n in (self.neighbors)(pos) {
+                    *counts.entry(n).or_default() += 1;
+                }
+            }
+        }
+        let quiet = S::default();
+        counts
+            .into_iter()
+            .map(|(pos, live)| (pos, rule.apply(world.get(pos).unwrap_or(&quiet), live)))
+            .filter(|(_, state)| *state != quiet)
+            .collect()
+    }
+}
+
+/// A cell of a space that looks the same under some group of symmetries.
+pub trait Orbit {
+    /// The representative of the cells that the symmetries map `self` to.
+    fn canonical(&self) -> Self;
+    /// How many cells share `self`'s representative.
+    fn weight(&self) -> u64;
+}
+
+/// A [`Sparse`] space whose live cells are always symmetric, stored as the
+/// set of [`Orbit::canonical`] live cells. Every world passed to it must be
+/// canonical.
+pub struct Symmetric<C, N> {
+    neighbors: N,
+    cell: PhantomData<C>,
+}
+impl<C, N> Symmetric<C, N>
+where
+    N: Fn(&C) -> Vec<C>,
+{
+    pub fn new(neighbors: N) -> Symmetric<C, N> {
+        Symmetric {
+            neighbors,
+            cell: PhantomData,
+        }
+    }
+}
+impl<C, N> Topology for Symmetric<C, N>
+where
+    C: Orbit + Eq + Hash + Clone,
+    N: Fn(&C) -> Vec<C>,
+{
+    type World = HashSet<C>;
+    type State = bool;
+
+    fn step<R: Rule<bool>>(&self, world: &HashSet<C>, rule: &R) -> HashSet<C> {
+        // Counting adjacent pairs between the orbits of `c` and `q` from
+        // either side: the live neighbors of `q` in `c`'s orbit number
+        // `weight(c) / weight(q)` for each neighbor of `c` in `q`'s orbit.
+        let mut pairs: HashMap<C, u64> = world.iter().map(|c| (c.clone(), 0)).collect();
+        for c in world {
+            for n in (self.neighbors)(c) {
+                *pairs.entry(n.canonical()).or_default() += c.weight();
+            }
+        }
+        pairs
+            .into_iter()
+            .filter(|(q, pairs)| rule.apply(&world.contains(q), (pairs / q.weight()) as usize))
+            .map(|(q, _)| q)
+            .collect()
+    }
+}
+
+/// A [`Rule`] applied over a [`Topology`].
+pub struct Automaton<T, R> {
+    pub topology: T,
+    pub rule: R,
+}
+impl<T, R> Automaton<T, R>
+where
+    T: Topology,
+    R: Rule<T::State>,
+{
+    pub fn new(topology: T, rule: R) -> Automaton<T, R> {
+        Automaton { topology, rule }
+    }
+    /// The generation after `world`.
+    pub fn step(&self, world: &T::World) -> T::World {
+        self.topology.step(world, &self.rule)
+    }
+    /// `world` and every generation after it.
+    pub fn generations(&self, world: T::World) -> impl Iterator<Item = T::World> + '_ {
+        iter::successors(Some(world), move |world| Some(self.step(world)))
+    }
+    /// The generation `n` steps after `world`.
+    pub fn run(&self, mut world: T::World, n: usize) -> T::World {
+        for _ in 0..n {
+            world = self.step(&world);
+        }
+        world
+    }
+    /// Steps until some generation repeats an earlier one, or until `limit`
+    /// steps have been taken.
+    pub fn settle(&self, mut world: T::World, limit: usize) -> Outcome<T::World>
+    where
+        T::World: Fingerprint + Clone,
+    {
+        // Generations by fingerprint, to find repeats without comparing
+        // every pair.
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut history: Vec<T::World> = Vec::new();
+        for generation in 0.. {
+            let earlier = seen.entry(world.fingerprint()).or_default();
+            if let Some(&start) = earlier.iter().find(|&&g| history[g] == world) {
+                let period = generation - start;
+                let world = history.swap_remove(start);
+                return if period == 1 {
+                    Outcome::Stable {
+                        generation: start,
+                        world,
+                    }
+                } else {
+                    Outcome::Cycle {
+                        transient: start,
+                        period,
+                        world,
+                    }
+                };
+            }
+            if generation == limit {
+                break;
+            }
+            earlier.push(generation);
+            let next = self.step(&world);
+            history.push(world);
+            world = next;
+        }
+        Outcome::Unsettled {
+            generations: limit,
+            world,
+        }
+    }
+}
+
+/// A hash of a whole generation, for spotting repeats.
+pub trait Fingerprint: PartialEq {
+    fn fingerprint(&self) -> u64;
+}
+impl<T: Hash + PartialEq> Fingerprint for Grid<T> {
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.width, self.height, &self.items).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+impl<C: Hash + Eq> Fingerprint for HashSet<C> {
+    fn fingerprint(&self) -> u64 {
+        // Sets iterate in any order, so combine the cells' hashes with an
+        // operation that ignores it.
+        self.iter()
+            .map(|c| {
+                let mut hasher = DefaultHasher::new();
+                c.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+}
+
+impl<T: Hash + PartialEq> Fingerprint for InfiniteGrid<T> {
+    fn fingerprint(&self) -> u64 {
+        // As for `HashSet`, in an order-independent way.
+        self.iter()
+            .map(|cell| {
+                let mut hasher = DefaultHasher::new();
+                cell.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+}
+
+/// How a run of an [`Automaton`] ended.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Outcome<W> {
+    /// Every generation from `generation` on is `world`.
+    Stable { generation: usize, world: W },
+    /// From generation `transient` on, the generations repeat every
+    /// `period > 1` steps; `world` is generation `transient`.
+    Cycle {
+        transient: usize,
+        period: usize,
+        world: W,
+    },
+    /// No generation repeated within `generations` steps; `world` is the
+    /// last one.
+    Unsettled { generations: usize, world: W },
+}
+impl<W> Outcome<W> {
+    /// The final world, if the run stabilized.
+    pub fn stable(self) -> Option<W> {
+        match self {
+            Outcome::Stable { world, .. } => Some(world),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{Automaton, Dense, LifeRule, Orbit, Outcome, Rule, Sparse, Symmetric, Unbounded};
+    use crate::grid::{Grid, InfiniteGrid, Pos};
+
+    fn life(&alive: &bool, n: usize) -> bool {
+        n == 3 || (alive && n == 2)
+    }
+
+    fn neighbors(&(i, j): &Pos) -> Vec<Pos> {
+        let mut ns = Vec::new();
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                if (di, dj) != (0, 0) {
+                    ns.push((i + di, j + dj));
+                }
+            }
+        }
+        ns
+    }
+
+    #[test]
+    fn rule_strings() {
+        let conway: LifeRule = "B3/S23".parse().unwrap();
+        assert_eq!(conway, LifeRule::new(&[3], &[2, 3]));
+        assert_eq!("b3/s32".parse(), Ok(conway));
+        assert_eq!(conway.to_string(), "B3/S23");
+        assert_eq!("B/S".parse::<LifeRule>().unwrap().to_string(), "B/S");
+        for n in 0..9 {
+            assert_eq!(conway.apply(&true, n), life(&true, n));
+            assert_eq!(conway.apply(&false, n), life(&false, n));
+        }
+        assert!(!conway.apply(&false, 500));
+        assert_eq!(
+            "S23/B3".parse::<LifeRule>(),
+            Err("invalid rule: S23/B3 (expected e.g. B3/S23)".to_owned())
+        );
+        assert!("B3S23".parse::<LifeRule>().is_err());
+        assert!("B3/S2x".parse::<LifeRule>().is_err());
+    }
+
+    #[test]
+    fn glider() {
+        let life = Automaton::new(
+            Sparse::new(neighbors),
+            "B3/S23".parse::<LifeRule>().unwrap(),
+        );
+        let glider: HashSet<Pos> = vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]
+            .into_iter()
+            .collect();
+        let moved: HashSet<Pos> = glider.iter().map(|&(i, j)| (i + 1, j + 1)).collect();
+        assert_eq!(life.run(glider, 4), moved);
+    }
+
+    #[test]
+    fn sparse() {
+        let life = Automaton::new(Sparse::new(neighbors), life);
+        let blinker: HashSet<Pos> = vec![(0, -1), (0, 0), (0, 1)].into_iter().collect();
+        let turned: HashSet<Pos> = vec![(-1, 0), (0, 0), (1, 0)].into_iter().collect();
+        assert_eq!(life.step(&blinker), turned);
+        assert_eq!(life.run(blinker.clone(), 2), blinker);
+        let generations: Vec<HashSet<Pos>> = life.generations(blinker.clone()).take(3).collect();
+        assert_eq!(generations, vec![blinker.clone(), turned, blinker]);
+        let block: HashSet<Pos> = vec![(0, 0), (0, 1), (1, 0), (1, 1)].into_iter().collect();
+        assert_eq!(
+            life.settle(block.clone(), 10),
+            Outcome::Stable {
+                generation: 0,
+                world: block
+            }
+        );
+    }
+
+    #[test]
+    fn dense() {
+        let neighbors = |grid: &Grid<bool>, pos| grid.neighbors8(pos).collect();
+        let life = Automaton::new(Dense::new(neighbors, |&alive| alive), life);
+        let blinker = Grid::new(3, 3, [false, true, false].repeat(3));
+        let turned = life.step(&blinker);
+        assert_eq!(
+            turned.items,
+            [vec![false; 3], vec![true; 3], vec![false; 3]].concat()
+        );
+        assert_eq!(life.run(blinker.clone(), 4), blinker);
+    }
+
+    #[test]
+    fn unbounded() {
+        let neighbors = |(i, j): Pos| neighbors(&(i, j));
+        let life = Automaton::new(Unbounded::new(neighbors, |&alive| alive), life);
+        let blinker: InfiniteGrid<bool> = vec![((0, -1), true), ((0, 0), true), ((0, 1), true)]
+            .into_iter()
+            .collect();
+        let turned = life.step(&blinker);
+        assert_eq!(turned.bounds(), Some(((-1, 0), (1, 0))));
+        assert_eq!(turned.len(), 3);
+        assert_eq!(
+            life.settle(blinker.clone(), 10),
+            Outcome::Cycle {
+                transient: 0,
+                period: 2,
+                world: blinker
+            }
+        );
+
+        // Brian's Brain: live cells always start dying, and dying ones turn
+        // off, so nothing but off cells is ever stored for long.
+        #[derive(Debug, Eq, PartialEq, Hash, Clone, Copy, Default)]
+        enum Brain {
+            #[default]
+            Off,
+            On,
+            Dying,
+        }
+        let brain = |state: &Brain, live| match state {
+            Brain::Off if live == 2 => Brain::On,
+            Brain::Off => Brain::Off,
+            Brain::On => Brain::Dying,
+            Brain::Dying => Brain::Off,
+        };
+        let brain = Automaton::new(Unbounded::new(neighbors, |&s| s == Brain::On), brain);
+        let lone: InfiniteGrid<Brain> = vec![((0, 0), Brain::On)].into_iter().collect();
+        let dying = brain.step(&lone);
+        assert_eq!(dying.get((0, 0)), Some(&Brain::Dying));
+        assert_eq!(dying.len(), 1);
+        assert_eq!(
+            brain.settle(lone, 10).stable().map(|world| world.len()),
+            Some(0)
+        );
+    }
+
+    // A cell on a line that is symmetric about 0.
+    #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+    struct Mirrored(i32);
+    impl Orbit for Mirrored {
+        fn canonical(&self) -> Mirrored {
+            Mirrored(self.0.abs())
+        }
+        fn weight(&self) -> u64 {
+            if self.0 == 0 {
+                1
+            } else {
+                2
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric() {
+        let line = |c: &Mirrored| vec![Mirrored(c.0 - 1), Mirrored(c.0 + 1)];
+        let rule: LifeRule = "B1/S1".parse().unwrap();
+        let full = Automaton::new(Sparse::new(line), rule);
+        let reduced = Automaton::new(Symmetric::new(line), rule);
+        let mut world: HashSet<Mirrored> = vec![Mirrored(-1), Mirrored(0), Mirrored(1)]
+            .into_iter()
+            .collect();
+        let mut canonical: HashSet<Mirrored> = world.iter().map(Orbit::canonical).collect();
+        for _ in 0..5 {
+            world = full.step(&world);
+            canonical = reduced.step(&canonical);
+            let expected: HashSet<Mirrored> = world.iter().map(Orbit::canonical).collect();
+            assert_eq!(canonical, expected);
+            let weight: u64 = canonical.iter().map(Orbit::weight).sum();
+            assert_eq!(weight as usize, world.len());
+        }
+    }
+
+    #[test]
+    fn settle() {
+        let life = Automaton::new(Sparse::new(neighbors), life);
+        // A lone cell dies, and then nothing ever changes.
+        let lone: HashSet<Pos> = vec![(0, 0)].into_iter().collect();
+        assert_eq!(
+            life.settle(lone, 10),
+            Outcome::Stable {
+                generation: 1,
+                world: HashSet::new()
+            }
+        );
+        let blinker: HashSet<Pos> = vec![(0, -1), (0, 0), (0, 1)].into_iter().collect();
+        assert_eq!(
+            life.settle(blinker.clone(), 10),
+            Outcome::Cycle {
+                transient: 0,
+                period: 2,
+                world: blinker
+            }
+        );
+        // A glider never repeats exactly.
+        let glider: HashSet<Pos> = vec![(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)]
+            .into_iter()
+            .collect();
+        let outcome = life.settle(glider.clone(), 8);
+        let moved: HashSet<Pos> = glider.iter().map(|&(i, j)| (i + 2, j + 2)).collect();
+        assert_eq!(
+            outcome,
+            Outcome::Unsettled {
+                generations: 8,
+                world: moved
+            }
+        );
+        assert_eq!(outcome.stable(), None);
+    }
+}
@@ -1,6 +1,78 @@
+//! Day 1: Report Repair.
+
+use itertools::Itertools;
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 1;
+
+/// Parses one expense entry per whitespace-separated token.
+pub fn parse(input: &str) -> Result<Vec<i32>, Error> {
+    input
+        .split_ascii_whitespace()
+        .map(|token| {
+            token
+                .parse::<i32>()
+                .map_err(|_| Error::parse(DAY, input, token, "an integer"))
+        })
+        .collect()
+}
+
+/// Product of the two entries that sum to 2020.
+pub fn solve1(entries: &[i32]) -> Option<i32> {
+    let (a, b) = entries
+        .iter()
+        .tuple_combinations()
+        .find(|(&a, &b)| a + b == 2020)?;
+    Some(a * b)
+}
+
+/// Product of the three entries that sum to 2020.
+pub fn solve2(entries: &[i32]) -> Option<i32> {
+    let (a, b, c) = entries
+        .iter()
+        .tuple_combinations()
+        .find(|(&a, &b, &c)| a + b + c == 2020)?;
+    Some(a * b * c)
+}
+
+/// [`Solution`] for day 1.
+pub struct Day01;
+impl Solution for Day01 {
+    type Input = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<i32> {
+        solve1(input)
+    }
+    fn part2(input: &Self::Input) -> Option<i32> {
+        solve2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use itertools::Itertools;
+    use super::{parse, solve1, solve2};
+    use crate::error::Error;
+
+    #[test]
+    fn parser() {
+        assert_eq!(parse("1721\n979").unwrap(), vec![1721, 979]);
+        assert_eq!(
+            parse("1721\n97x9").unwrap_err(),
+            Error::Parse {
+                day: 1,
+                line: 2,
+                column: 1,
+                expected: "an integer".to_owned(),
+                found: "`97x9`".to_owned(),
+            }
+        );
+    }
 
     #[test]
     fn small1() {
@@ -12,46 +84,12 @@ mod test {
             675
             1456
         "#;
-        let entries: Vec<i32> = input
-            .split_ascii_whitespace()
-            .map(|token| token.parse::<i32>().unwrap())
-            .collect();
-
-        let (a, b) = entries
-            .iter()
-            .tuple_combinations()
-            .find(|(&a, &b)| a + b == 2020)
-            .unwrap();
-        assert_eq!(a * b, 514579);
-    }
-
-    #[test]
-    fn normal1() {
-        let input = std::fs::read_to_string("data/day01.input").unwrap();
-        let entries: Vec<i32> = input
-            .split_ascii_whitespace()
-            .map(|token| token.parse::<i32>().unwrap())
-            .collect();
-        let (a, b) = entries
-            .iter()
-            .tuple_combinations()
-            .find(|(&a, &b)| a + b == 2020)
-            .unwrap();
-        assert_eq!(a * b, 996075);
+        assert_eq!(solve1(&parse(input).unwrap()).unwrap(), 514579);
     }
 
     #[test]
-    fn normal2() {
-        let input = std::fs::read_to_string("data/day01.input").unwrap();
-        let entries: Vec<i32> = input
-            .split_ascii_whitespace()
-            .map(|token| token.parse::<i32>().unwrap())
-            .collect();
-        let (a, b, c) = entries
-            .iter()
-            .tuple_combinations()
-            .find(|(&a, &b, &c)| a + b + c == 2020)
-            .unwrap();
-        assert_eq!(a * b * c, 51810360);
+    fn small2() {
+        let input = "1721 979 366 299 675 1456";
+        assert_eq!(solve2(&parse(input).unwrap()).unwrap(), 241861950);
     }
 }
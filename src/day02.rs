@@ -1,20 +1,27 @@
+//! Day 2: Password Philosophy.
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use std::str::FromStr;
 
+use crate::{error::Error, solution::Solution};
+
+/// The corporate policy attached to a password: `lo-hi target`.
 #[derive(Debug, Eq, PartialEq)]
-struct Policy {
-    target: char,
-    lo: usize,
-    hi: usize,
+pub struct Policy {
+    pub target: char,
+    pub lo: usize,
+    pub hi: usize,
 }
-struct Input {
-    policy: Policy,
-    password: String,
+/// One line of the password database.
+pub struct Input {
+    pub policy: Policy,
+    pub password: String,
 }
 impl Input {
-    fn is_valid(&self) -> bool {
+    /// `target` occurs between `lo` and `hi` times (inclusive).
+    pub fn is_valid(&self) -> bool {
         (self.policy.lo..=self.policy.hi).contains(
             &self
                 .password
@@ -24,35 +31,82 @@ impl Input {
         )
     }
 
-    fn is_valid_2(&self) -> bool {
-        let a = self.password.chars().nth(self.policy.lo - 1).unwrap();
-        let b = self.password.chars().nth(self.policy.hi - 1).unwrap();
-        (a == self.policy.target) ^ (b == self.policy.target)
+    /// Exactly one of the (1-indexed) positions `lo` and `hi` holds `target`.
+    pub fn is_valid_2(&self) -> bool {
+        let target = Some(self.policy.target);
+        let a = self.password.chars().nth(self.policy.lo - 1);
+        let b = self.password.chars().nth(self.policy.hi - 1);
+        (a == target) ^ (b == target)
     }
 }
 
+const DAY: u8 = 2;
+
 lazy_static! {
     static ref PATTERN: Regex =
-        Regex::new(r"(?P<lo>\d+)-(?P<hi>\d+) (?P<target>\w): (?P<password>\w+)").unwrap();
+        Regex::new(r"^(?P<lo>\d+)-(?P<hi>\d+) (?P<target>\w): (?P<password>\w+)$").unwrap();
 }
 impl FromStr for Input {
-    type Err = String;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Input, Self::Err> {
-        let m = PATTERN.captures(s).ok_or(s.to_owned())?;
-        Ok(Input {
-            policy: Policy {
-                target: m.name("target").unwrap().as_str().chars().next().unwrap(),
-                lo: m.name("lo").unwrap().as_str().parse().unwrap(),
-                hi: m.name("hi").unwrap().as_str().parse().unwrap(),
-            },
-            password: m.name("password").unwrap().as_str().to_owned(),
-        })
+        parse_line(s, s.trim())
+    }
+}
+
+/// Parses one `lo-hi target: password` entry per line.
+pub fn parse(input: &str) -> Result<Vec<Input>, Error> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_line(input, line))
+        .collect()
+}
+
+// `line` is a subslice of `input`, which is only used to locate errors.
+fn parse_line(input: &str, line: &str) -> Result<Input, Error> {
+    let m = PATTERN
+        .captures(line)
+        .ok_or_else(|| Error::parse(DAY, input, line, "a policy like `1-3 a: password`"))?;
+    let bound = |name: &str| {
+        let raw = m.name(name).unwrap().as_str();
+        raw.parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| Error::parse(DAY, input, raw, "a positive integer"))
+    };
+    Ok(Input {
+        policy: Policy {
+            target: m.name("target").unwrap().as_str().chars().next().unwrap(),
+            lo: bound("lo")?,
+            hi: bound("hi")?,
+        },
+        password: m.name("password").unwrap().as_str().to_owned(),
+    })
+}
+
+/// [`Solution`] for day 2.
+pub struct Day02;
+impl Solution for Day02 {
+    type Input = Vec<Input>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(input.iter().filter(|input| input.is_valid()).count())
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(input.iter().filter(|input| input.is_valid_2()).count())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Input, Policy};
+    use super::{parse, Input, Policy};
+    use crate::error::Error;
 
     #[test]
     fn parser() {
@@ -66,6 +120,16 @@ mod test {
             }
         );
         assert_eq!(password, "abcde");
+        assert_eq!(
+            parse("1-3 a: abcde\n0-3 b: cdefg").err().unwrap(),
+            Error::Parse {
+                day: 2,
+                line: 2,
+                column: 1,
+                expected: "a positive integer".to_owned(),
+                found: "`0-3`".to_owned(),
+            }
+        );
     }
 
     #[test]
@@ -84,18 +148,6 @@ mod test {
         assert_eq!(count, 2);
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day02.input").unwrap();
-        let count = raw
-            .trim()
-            .lines()
-            .map(|line| line.parse::<Input>().unwrap())
-            .filter(|input| input.is_valid())
-            .count();
-        assert_eq!(count, 467);
-    }
-
     #[test]
     fn small2() {
         let raw = r"
@@ -111,16 +163,4 @@ mod test {
             .count();
         assert_eq!(count, 1);
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day02.input").unwrap();
-        let count = raw
-            .trim()
-            .lines()
-            .map(|line| line.parse::<Input>().unwrap())
-            .filter(|input| input.is_valid_2())
-            .count();
-        assert_eq!(count, 441);
-    }
 }
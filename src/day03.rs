@@ -1,22 +1,80 @@
-fn count_trees(grid: &str, vx: usize, vy: usize) -> usize {
-    let lines: Vec<String> = grid.split_ascii_whitespace().map(String::from).collect();
-    let width = lines[0].len();
-    let mut count = 0;
-    let mut row = 0;
-    let mut col = 0;
-    while row < lines.len() {
-        if lines[row].chars().nth(col).unwrap() == '#' {
-            count += 1;
-        }
-        row = row + vy;
-        col = (col + vx) % width;
+//! Day 3: Toboggan Trajectory.
+
+use crate::{error::Error, grid::Grid, solution::Solution};
+
+const DAY: u8 = 3;
+
+/// Counts the trees hit sliding down `grid` with slope right `vx`, down `vy`,
+/// wrapping around horizontally; `None` if `vy` is 0, as the slide never
+/// reaches the bottom.
+pub fn count_trees(grid: &Grid<bool>, vx: usize, vy: usize) -> Option<usize> {
+    if vy == 0 {
+        return None;
+    }
+    let trees = (0..grid.height)
+        .step_by(vy)
+        .enumerate()
+        .filter(|&(step, row)| grid.get_wrapped((row as i32, (step * vx) as i32)) == Some(&true))
+        .count();
+    Some(trees)
+}
+
+/// Parses a rectangular map of `.` (open) and `#` (tree).
+pub fn parse(input: &str) -> Result<Grid<bool>, Error> {
+    Grid::parse(DAY, input, "`.` or `#`", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
+}
+
+/// [`Solution`] for day 3.
+pub struct Day03;
+impl Solution for Day03 {
+    type Input = Grid<bool>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        count_trees(input, 3, 1)
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        let slopes = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+        slopes
+            .iter()
+            .map(|&(vx, vy)| count_trees(input, vx, vy))
+            .product()
     }
-    count
 }
 
 #[cfg(test)]
 mod test {
-    use super::count_trees;
+    use super::{count_trees, parse};
+    use crate::error::Error;
+
+    #[test]
+    fn parser() {
+        let grid = parse("..#\n#..").unwrap();
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_eq!(grid.items, vec![false, false, true, true, false, false]);
+        assert_eq!(
+            parse("..#\n#.").unwrap_err().to_string(),
+            "day 3, line 2, column 3: expected a row of width 3, found end of input"
+        );
+        assert_eq!(
+            parse("..#\n#o.").unwrap_err(),
+            Error::Parse {
+                day: 3,
+                line: 2,
+                column: 2,
+                expected: "`.` or `#`".to_owned(),
+                found: "`o.`".to_owned(),
+            }
+        );
+    }
 
     #[test]
     fn small1() {
@@ -33,25 +91,8 @@ mod test {
             #...##....#
             .#..#...#.#
         ";
-        let count = count_trees(raw, 3, 1);
-        assert_eq!(count, 7);
-    }
-
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day03.input").unwrap();
-        let count = count_trees(&raw, 3, 1);
-        assert_eq!(count, 214);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day03.input").unwrap();
-        let v11 = count_trees(&raw, 1, 1);
-        let v31 = count_trees(&raw, 3, 1);
-        let v51 = count_trees(&raw, 5, 1);
-        let v71 = count_trees(&raw, 7, 1);
-        let v12 = count_trees(&raw, 1, 2);
-        assert_eq!(v11 * v31 * v51 * v71 * v12, 8336352024);
+        let grid = parse(raw).unwrap();
+        assert_eq!(count_trees(&grid, 3, 1), Some(7));
+        assert_eq!(count_trees(&grid, 1, 0), None);
     }
 }
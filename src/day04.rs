@@ -1,8 +1,13 @@
+//! Day 4: Passport Processing.
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
-type Passport = HashMap<String, String>;
+use crate::{error::Error, solution::Solution};
+
+/// Field name to raw value.
+pub type Passport = HashMap<String, String>;
 
 lazy_static! {
     static ref PASSPORT: Regex = Regex::new(r"([[:alpha:]]+):([^[[:space:]]]+)").unwrap();
@@ -15,7 +20,8 @@ lazy_static! {
     static ref ECL: Regex = Regex::new(r"^amb|blu|brn|gry|grn|hzl|oth$").unwrap();
     static ref PID: Regex = Regex::new(r"^[[:digit:]]{9}$").unwrap();
 }
-fn parse_passports(input: &str) -> Vec<Passport> {
+/// Parses blank-line separated batches of `key:value` fields.
+pub fn parse_passports(input: &str) -> Vec<Passport> {
     let mut passports = vec![];
     let mut cur = HashMap::new();
     for line in input.lines().map(|s| s.trim()) {
@@ -44,12 +50,14 @@ lazy_static! {
         .map(String::from)
         .collect();
 }
-fn has_required_fields(passport: &Passport) -> bool {
+/// Every required field is present (`cid` is optional).
+pub fn has_required_fields(passport: &Passport) -> bool {
     let ks: HashSet<String> = passport.keys().map(String::from).collect();
     ks.is_superset(&REQUIRED)
 }
 
-fn validate(passport: &Passport) -> Result<(), &str> {
+/// Checks every required field's value, returning the first invalid key.
+pub fn validate(passport: &Passport) -> Result<(), &str> {
     let check = |key: &'static str, pattern: &Regex| {
         passport.get(key).filter(|v| pattern.is_match(v)).ok_or(key)
     };
@@ -63,6 +71,24 @@ fn validate(passport: &Passport) -> Result<(), &str> {
     Ok(())
 }
 
+/// [`Solution`] for day 4.
+pub struct Day04;
+impl Solution for Day04 {
+    type Input = Vec<Passport>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(parse_passports(input))
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(input.iter().filter(|p| has_required_fields(p)).count())
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(input.iter().filter(|p| validate(p).is_ok()).count())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{has_required_fields, parse_passports, validate};
@@ -90,14 +116,6 @@ mod test {
         assert_eq!(valid_count, 2);
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day04.input").unwrap();
-        let parsed = parse_passports(&raw);
-        let valid_count = parsed.into_iter().filter(has_required_fields).count();
-        assert_eq!(valid_count, 222);
-    }
-
     #[test]
     fn small2() {
         let valid = r"
@@ -126,12 +144,4 @@ mod test {
             assert!(validate(&p).is_err(), "should be invalid: {:?}", p);
         }
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day04.input").unwrap();
-        let parsed = parse_passports(&raw);
-        let valid_count = parsed.into_iter().filter(|p| validate(p).is_ok()).count();
-        assert_eq!(valid_count, 140);
-    }
 }
@@ -1,4 +1,11 @@
-fn get_seat_number(pass: &str) -> Option<usize> {
+//! Day 5: Binary Boarding.
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 5;
+
+/// Decodes a boarding pass such as `FBFBBFFRLR` into its seat ID.
+pub fn get_seat_number(pass: &str) -> Option<usize> {
     let mut acc = 0;
     for ch in pass.chars() {
         let v = match ch {
@@ -11,8 +18,26 @@ fn get_seat_number(pass: &str) -> Option<usize> {
     Some(acc)
 }
 
-// Find the first contiguous missing value from `xs`
-fn find_missing(xs: &[usize]) -> Option<usize> {
+/// Decodes one boarding pass per line.
+pub fn parse(input: &str) -> Result<Vec<usize>, Error> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.find(|c| !"FBLR".contains(c)) {
+            Some(bad) => Err(Error::parse(
+                DAY,
+                input,
+                &line[bad..],
+                "`F`, `B`, `L` or `R`",
+            )),
+            None => Ok(get_seat_number(line).unwrap()),
+        })
+        .collect()
+}
+
+/// Find the first contiguous missing value from `xs`
+pub fn find_missing(xs: &[usize]) -> Option<usize> {
     if xs.is_empty() {
         return None;
     }
@@ -26,9 +51,45 @@ fn find_missing(xs: &[usize]) -> Option<usize> {
     None
 }
 
+/// [`Solution`] for day 5.
+pub struct Day05;
+impl Solution for Day05 {
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        input.iter().copied().max()
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        let mut seat_numbers = input.clone();
+        seat_numbers.sort_unstable();
+        find_missing(&seat_numbers)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{find_missing, get_seat_number};
+    use super::{find_missing, get_seat_number, parse};
+    use crate::error::Error;
+
+    #[test]
+    fn parser() {
+        assert_eq!(parse("BFFFBBFRRR\nFFFBBBFRRR").unwrap(), vec![567, 119]);
+        assert_eq!(
+            parse("BFFFBBFRRR\nFFFBXBFRRR").unwrap_err(),
+            Error::Parse {
+                day: 5,
+                line: 2,
+                column: 5,
+                expected: "`F`, `B`, `L` or `R`".to_owned(),
+                found: "`XBFRRR`".to_owned(),
+            }
+        );
+    }
 
     #[test]
     fn small1() {
@@ -38,23 +99,8 @@ mod test {
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day05.input").unwrap();
-        let seat_numbers: Vec<usize> = raw
-            .lines()
-            .map(|line| get_seat_number(line.trim()).unwrap())
-            .collect();
-        assert_eq!(seat_numbers.into_iter().max().unwrap(), 806);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day05.input").unwrap();
-        let mut seat_numbers: Vec<usize> = raw
-            .lines()
-            .map(|line| get_seat_number(line.trim()).unwrap())
-            .collect();
-        seat_numbers.sort();
-        assert_eq!(find_missing(&seat_numbers).unwrap(), 562);
+    fn small2() {
+        assert_eq!(find_missing(&[3, 4, 5, 7, 8]), Some(6));
+        assert_eq!(find_missing(&[3, 4, 5]), None);
     }
 }
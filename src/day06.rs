@@ -1,6 +1,11 @@
+//! Day 6: Custom Customs.
+
 use std::collections::HashSet;
 
-fn count_any(input: &str) -> usize {
+use crate::{error::Error, solution::Solution};
+
+/// Sums, per group, the questions anyone answered "yes" to.
+pub fn count_any(input: &str) -> usize {
     input
         .trim()
         .split("\n\n")
@@ -14,7 +19,8 @@ fn count_any(input: &str) -> usize {
         .sum()
 }
 
-fn count_all(input: &str) -> usize {
+/// Sums, per group, the questions everyone answered "yes" to.
+pub fn count_all(input: &str) -> usize {
     input
         .trim()
         .split("\n\n")
@@ -31,6 +37,24 @@ fn count_all(input: &str) -> usize {
         .sum()
 }
 
+/// [`Solution`] for day 6.
+pub struct Day06;
+impl Solution for Day06 {
+    type Input = String;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok(input.to_owned())
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(count_any(input))
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(count_all(input))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::{count_all, count_any};
@@ -58,20 +82,8 @@ mod test {
         assert_eq!(count_any(SMALL), 11);
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day06.input").unwrap();
-        assert_eq!(count_any(&raw), 6742);
-    }
-
     #[test]
     fn small2() {
         assert_eq!(count_all(SMALL), 6);
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day06.input").unwrap();
-        assert_eq!(count_all(&raw), 3447);
-    }
 }
@@ -1,3 +1,5 @@
+//! Day 7: Handy Haversacks.
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{
@@ -5,46 +7,68 @@ use std::{
     str::FromStr,
 };
 
-type Bag = String;
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 7;
+
+/// A bag color, e.g. `shiny gold`.
+pub type Bag = String;
+/// One rule: `bag` must directly contain each `(count, bag)` in `contents`.
 #[derive(Debug, Eq, PartialEq)]
-struct Relation {
-    bag: Bag,
-    contents: Vec<(usize, Bag)>,
+pub struct Relation {
+    pub bag: Bag,
+    pub contents: Vec<(usize, Bag)>,
 }
 lazy_static! {
     static ref TOP_LEVEL: Regex = Regex::new(r"^(.+?) bags contain (.+).$").unwrap();
     static ref CONTENT: Regex = Regex::new(r"^([[:digit:]]+) (.+) bags?$").unwrap();
 }
 impl FromStr for Relation {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let top = TOP_LEVEL
-            .captures(s.trim())
-            .ok_or(format!("invalid format: {}", s))?;
-        let bag: Bag = top.get(1).unwrap().as_str().to_owned();
-        let right = top.get(2).unwrap().as_str();
-        let mut contents = vec![];
-        if right != "no other bags" {
-            for s1 in right.split(", ") {
-                let content = CONTENT
-                    .captures(s1)
-                    .ok_or(format!("invalid content: {}", s1))?;
-                let count = {
-                    let count = content.get(1).unwrap().as_str();
-                    count
-                        .parse::<usize>()
-                        .map_err(|_| format!(r"invalid count: {}", count))?
-                };
-                let bag = content.get(2).unwrap().as_str().to_owned();
-                contents.push((count, bag));
-            }
+        parse_line(s, s.trim())
+    }
+}
+
+/// Parses one rule per line.
+pub fn parse(input: &str) -> Result<Vec<Relation>, Error> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| parse_line(input, line))
+        .collect()
+}
+
+// `line` is a subslice of `input`, which is only used to locate errors.
+fn parse_line(input: &str, line: &str) -> Result<Relation, Error> {
+    let top = TOP_LEVEL
+        .captures(line)
+        .ok_or_else(|| Error::parse(DAY, input, line, "`<color> bags contain <contents>.`"))?;
+    let bag: Bag = top.get(1).unwrap().as_str().to_owned();
+    let right = top.get(2).unwrap().as_str();
+    let mut contents = vec![];
+    if right != "no other bags" {
+        for s1 in right.split(", ") {
+            let content = CONTENT
+                .captures(s1)
+                .ok_or_else(|| Error::parse(DAY, input, s1, "`<count> <color> bag(s)`"))?;
+            let count = {
+                let count = content.get(1).unwrap().as_str();
+                count
+                    .parse::<usize>()
+                    .map_err(|_| Error::parse(DAY, input, count, "a bag count"))?
+            };
+            let bag = content.get(2).unwrap().as_str().to_owned();
+            contents.push((count, bag));
         }
-        Ok(Relation { bag, contents })
     }
+    Ok(Relation { bag, contents })
 }
 
-fn find_containers(relations: &[Relation], target: Bag) -> HashSet<Bag> {
+/// Every bag that eventually contains `target`.
+pub fn find_containers(relations: &[Relation], target: Bag) -> HashSet<Bag> {
     let mut parents: HashMap<Bag, Vec<Bag>> = HashMap::new();
     for r in relations {
         for (_, child) in &r.contents {
@@ -67,7 +91,8 @@ fn find_containers(relations: &[Relation], target: Bag) -> HashSet<Bag> {
     containers
 }
 
-fn count_contents(relations: &[Relation], target: Bag) -> usize {
+/// Total number of bags inside `target`.
+pub fn count_contents(relations: &[Relation], target: Bag) -> usize {
     let contents = relations
         .iter()
         .find(|r| r.bag == target)
@@ -79,9 +104,28 @@ fn count_contents(relations: &[Relation], target: Bag) -> usize {
         .sum::<usize>()
 }
 
+/// [`Solution`] for day 7.
+pub struct Day07;
+impl Solution for Day07 {
+    type Input = Vec<Relation>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(find_containers(input, "shiny gold".to_owned()).len())
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(count_contents(input, "shiny gold".to_owned()))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{count_contents, find_containers, Relation};
+    use super::{count_contents, find_containers, parse, Relation};
+    use crate::error::Error;
 
     const SMALL: &str = r"
         light red bags contain 1 bright white bag, 2 muted yellow bags.
@@ -122,6 +166,17 @@ mod test {
                 contents: vec![]
             }
         );
+        assert_eq!(
+            parse("faded blue bags contain no other bags.\nshiny gold bags contain x dark olive bags.")
+                .unwrap_err(),
+            Error::Parse {
+                day: 7,
+                line: 2,
+                column: 25,
+                expected: "`<count> <color> bag(s)`".to_owned(),
+                found: "`x`".to_owned(),
+            }
+        );
     }
 
     #[test]
@@ -137,20 +192,6 @@ mod test {
         );
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day07.input").unwrap();
-        let relations: Vec<Relation> = raw
-            .trim()
-            .lines()
-            .map(|l| l.parse::<Relation>().unwrap())
-            .collect();
-        assert_eq!(
-            find_containers(&relations, "shiny gold".to_owned()).len(),
-            197
-        );
-    }
-
     #[test]
     fn small2() {
         let relations: Vec<Relation> = SMALL
@@ -160,15 +201,4 @@ mod test {
             .collect();
         assert_eq!(count_contents(&relations, "shiny gold".to_owned()), 32);
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day07.input").unwrap();
-        let relations: Vec<Relation> = raw
-            .trim()
-            .lines()
-            .map(|l| l.parse::<Relation>().unwrap())
-            .collect();
-        assert_eq!(count_contents(&relations, "shiny gold".to_owned()), 85324);
-    }
 }
@@ -1,95 +1,444 @@
+//! Day 8: Handheld Halting.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    io::{self, BufRead, Write},
+    iter::FromIterator,
+    ops::Range,
+};
+
+use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while},
-    character::complete::{multispace0, multispace1},
-    combinator::{all_consuming, map_res, value},
-    multi::separated_list1,
-    sequence::{delimited, separated_pair},
+    character::complete::multispace1,
+    combinator::{map_res, value},
+    sequence::separated_pair,
     IResult,
 };
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct Instruction {
-    opcode: OpCode,
-    arg: i32,
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 8;
+
+/// One of the console's registers, as an index into
+/// [`Interpreter::registers`].
+pub type Register = usize;
+/// How many registers the console has.
+pub const REGISTERS: usize = 8;
+/// The accumulator, the only register boot code uses.
+pub const ACC: Register = 0;
+/// Register names in assembly: `acc`, then `r1` to `r7`.
+pub const REGISTER_NAMES: [&str; REGISTERS] = ["acc", "r1", "r2", "r3", "r4", "r5", "r6", "r7"];
+
+/// A value read by an instruction.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Operand {
+    Reg(Register),
+    Imm(i32),
 }
-impl Instruction {
-    fn nop(arg: i32) -> Instruction {
-        Instruction {
-            opcode: OpCode::Nop,
-            arg,
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Reg(r) => f.write_str(REGISTER_NAMES[*r]),
+            Operand::Imm(n) => write!(f, "{}", n),
         }
     }
-    fn jmp(arg: i32) -> Instruction {
-        Instruction {
-            opcode: OpCode::Jump,
-            arg,
+}
+
+/// An arithmetic instruction, `op reg operand`, storing `reg op operand` in
+/// `reg`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+impl ArithOp {
+    pub const ALL: [ArithOp; 5] = [
+        ArithOp::Add,
+        ArithOp::Sub,
+        ArithOp::Mul,
+        ArithOp::Div,
+        ArithOp::Mod,
+    ];
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            ArithOp::Add => "add",
+            ArithOp::Sub => "sub",
+            ArithOp::Mul => "mul",
+            ArithOp::Div => "div",
+            ArithOp::Mod => "mod",
         }
     }
-    fn acc(arg: i32) -> Instruction {
-        Instruction {
-            opcode: OpCode::Acc,
-            arg,
+    /// `a op b`, wrapping on overflow; `None` when dividing by zero.
+    pub fn apply(self, a: i32, b: i32) -> Option<i32> {
+        match self {
+            ArithOp::Add => Some(a.wrapping_add(b)),
+            ArithOp::Sub => Some(a.wrapping_sub(b)),
+            ArithOp::Mul => Some(a.wrapping_mul(b)),
+            ArithOp::Div => (b != 0).then(|| a.wrapping_div(b)),
+            ArithOp::Mod => (b != 0).then(|| a.wrapping_rem(b)),
         }
     }
 }
+
+/// The condition of a conditional jump, `op reg offset`.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
-enum OpCode {
-    Nop,
-    Jump,
-    Acc,
+pub enum Condition {
+    Zero,
+    NonZero,
+    Positive,
+    Negative,
+}
+impl Condition {
+    pub const ALL: [Condition; 4] = [
+        Condition::Zero,
+        Condition::NonZero,
+        Condition::Positive,
+        Condition::Negative,
+    ];
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Condition::Zero => "jz",
+            Condition::NonZero => "jnz",
+            Condition::Positive => "jgz",
+            Condition::Negative => "jlz",
+        }
+    }
+    pub fn holds(self, value: i32) -> bool {
+        match self {
+            Condition::Zero => value == 0,
+            Condition::NonZero => value != 0,
+            Condition::Positive => value > 0,
+            Condition::Negative => value < 0,
+        }
+    }
 }
 
-fn parse_instructions(input: &str) -> Result<Vec<Instruction>, String> {
-    let (_, vs) = all_consuming(delimited(multispace0, instructions_parser, multispace0))(input)
-        .map_err(|_| input.to_owned())?;
-    Ok(vs)
+/// A single instruction, e.g. `jmp -4`. Jump offsets are relative to the
+/// jump itself.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Instruction {
+    /// `nop`: does nothing with its argument.
+    Nop(i32),
+    /// `acc`: adds its argument to the accumulator.
+    Acc(i32),
+    /// `jmp`: jumps unconditionally.
+    Jump(i32),
+    /// `set reg operand`.
+    Set(Register, Operand),
+    Arith(ArithOp, Register, Operand),
+    Branch(Condition, Register, i32),
+    /// `in reg`: takes the next input value.
+    In(Register),
+    /// `out operand`: appends to the output.
+    Out(Operand),
+    /// `hlt`: completes the program.
+    Halt,
+}
+impl Instruction {
+    /// The `jmp` for a `nop` and the `nop` for a `jmp`, keeping the argument.
+    pub fn swapped(self) -> Option<Instruction> {
+        match self {
+            Instruction::Nop(n) => Some(Instruction::Jump(n)),
+            Instruction::Jump(n) => Some(Instruction::Nop(n)),
+            _ => None,
+        }
+    }
 }
-fn instructions_parser(input: &str) -> IResult<&str, Vec<Instruction>> {
-    separated_list1(multispace1, instruction_parser)(input)
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reg = |r: &Register| REGISTER_NAMES[*r];
+        match self {
+            Instruction::Nop(n) => write!(f, "nop {:+}", n),
+            Instruction::Acc(n) => write!(f, "acc {:+}", n),
+            Instruction::Jump(n) => write!(f, "jmp {:+}", n),
+            Instruction::Set(r, x) => write!(f, "set {} {}", reg(r), x),
+            Instruction::Arith(op, r, x) => write!(f, "{} {} {}", op.mnemonic(), reg(r), x),
+            Instruction::Branch(cond, r, n) => {
+                write!(f, "{} {} {:+}", cond.mnemonic(), reg(r), n)
+            }
+            Instruction::In(r) => write!(f, "in {}", reg(r)),
+            Instruction::Out(x) => write!(f, "out {}", x),
+            Instruction::Halt => f.write_str("hlt"),
+        }
+    }
+}
+
+/// Parses whitespace-separated `op arg` pairs of boot code.
+pub fn parse_instructions(input: &str) -> Result<Vec<Instruction>, Error> {
+    let mut instructions = Vec::new();
+    let mut rest = input.trim_start();
+    loop {
+        let (tail, instruction) = instruction_parser(rest)
+            .map_err(|e| Error::nom(DAY, input, e, "an instruction like `acc +1`"))?;
+        instructions.push(instruction);
+        if !tail.is_empty() && !tail.starts_with(char::is_whitespace) {
+            return Err(Error::parse(DAY, input, tail, "whitespace"));
+        }
+        rest = tail.trim_start();
+        if rest.is_empty() {
+            return Ok(instructions);
+        }
+    }
 }
 
 fn instruction_parser(input: &str) -> IResult<&str, Instruction> {
     nom::combinator::map(
         separated_pair(opcode_parser, multispace1, arg_parser),
-        |(opcode, arg)| Instruction { opcode, arg },
+        |(opcode, arg)| opcode(arg),
     )(input)
 }
-fn opcode_parser(input: &str) -> IResult<&str, OpCode> {
+fn opcode_parser(input: &str) -> IResult<&str, fn(i32) -> Instruction> {
     alt((
-        value(OpCode::Nop, tag("nop")),
-        value(OpCode::Jump, tag("jmp")),
-        value(OpCode::Acc, tag("acc")),
+        value(Instruction::Nop as fn(i32) -> Instruction, tag("nop")),
+        value(Instruction::Jump as fn(i32) -> Instruction, tag("jmp")),
+        value(Instruction::Acc as fn(i32) -> Instruction, tag("acc")),
     ))(input)
 }
 fn arg_parser(input: &str) -> IResult<&str, i32> {
     map_res(
-        take_while(|c: char| c == '+' || c == '-' || c.is_digit(10)),
+        take_while(|c: char| c == '+' || c == '-' || c.is_ascii_digit()),
         |s: &str| s.parse(),
     )(input)
 }
 
+/// Assembles a program, one instruction per line.
+///
+/// Besides boot code's `nop`, `acc` and `jmp`, lines may hold `set`, `add`,
+/// `sub`, `mul`, `div`, `mod`, the conditional jumps `jz`, `jnz`, `jgz` and
+/// `jlz`, `in`, `out` and `hlt`. Jumps take a relative offset or a label,
+/// defined by starting a line with `name:`. Anything after `#` or `;` is a
+/// comment.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, Error> {
+    // Split off labels and comments first, so that jumps can refer forwards.
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut lines = Vec::new();
+    for line in source.lines() {
+        let code = line.split(['#', ';']).next().unwrap();
+        let mut tokens: Vec<&str> = code.split_whitespace().collect();
+        while let Some(label) = tokens.first().and_then(|t| t.strip_suffix(':')) {
+            if !is_identifier(label) {
+                return Err(Error::parse(DAY, source, tokens[0], "a label name"));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(Error::parse(DAY, source, tokens[0], "a new label"));
+            }
+            tokens.remove(0);
+        }
+        if !tokens.is_empty() {
+            lines.push((tokens, &code[code.trim_end().len()..]));
+        }
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(pc, (tokens, end))| {
+            let mut operands = Operands {
+                source,
+                tokens: &tokens[1..],
+                end,
+                labels: &labels,
+                pc,
+            };
+            let instruction = match tokens[0] {
+                "nop" => Instruction::Nop(operands.number()?),
+                "acc" => Instruction::Acc(operands.number()?),
+                "jmp" => Instruction::Jump(operands.target()?),
+                "set" => Instruction::Set(operands.register()?, operands.operand()?),
+                "in" => Instruction::In(operands.register()?),
+                "out" => Instruction::Out(operands.operand()?),
+                "hlt" => Instruction::Halt,
+                mnemonic => {
+                    if let Some(&op) = ArithOp::ALL.iter().find(|op| op.mnemonic() == mnemonic) {
+                        Instruction::Arith(op, operands.register()?, operands.operand()?)
+                    } else if let Some(&cond) =
+                        Condition::ALL.iter().find(|c| c.mnemonic() == mnemonic)
+                    {
+                        Instruction::Branch(cond, operands.register()?, operands.target()?)
+                    } else {
+                        return Err(Error::parse(DAY, source, tokens[0], "an instruction"));
+                    }
+                }
+            };
+            operands.finish()?;
+            Ok(instruction)
+        })
+        .collect()
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// The operands of one line of assembly, consumed left to right.
+struct Operands<'a> {
+    source: &'a str,
+    tokens: &'a [&'a str],
+    // Where the line's code ends, for errors about missing operands.
+    end: &'a str,
+    labels: &'a HashMap<&'a str, usize>,
+    pc: usize,
+}
+impl<'a> Operands<'a> {
+    fn next(&mut self, expected: &str) -> Result<&'a str, Error> {
+        let (&token, rest) = self
+            .tokens
+            .split_first()
+            .ok_or_else(|| Error::parse(DAY, self.source, self.end, expected))?;
+        self.tokens = rest;
+        Ok(token)
+    }
+    fn number(&mut self) -> Result<i32, Error> {
+        let token = self.next("a number")?;
+        token
+            .parse()
+            .map_err(|_| Error::parse(DAY, self.source, token, "a number"))
+    }
+    fn register(&mut self) -> Result<Register, Error> {
+        let token = self.next("a register")?;
+        REGISTER_NAMES
+            .iter()
+            .position(|&name| name == token)
+            .ok_or_else(|| Error::parse(DAY, self.source, token, "a register"))
+    }
+    fn operand(&mut self) -> Result<Operand, Error> {
+        let token = self.next("a register or number")?;
+        if let Some(r) = REGISTER_NAMES.iter().position(|&name| name == token) {
+            Ok(Operand::Reg(r))
+        } else {
+            token
+                .parse()
+                .map(Operand::Imm)
+                .map_err(|_| Error::parse(DAY, self.source, token, "a register or number"))
+        }
+    }
+    fn target(&mut self) -> Result<i32, Error> {
+        let token = self.next("an offset or label")?;
+        if let Ok(offset) = token.parse() {
+            Ok(offset)
+        } else if let Some(&target) = self.labels.get(token) {
+            Ok(target as i32 - self.pc as i32)
+        } else {
+            Err(Error::parse(DAY, self.source, token, "an offset or label"))
+        }
+    }
+    fn finish(&self) -> Result<(), Error> {
+        match self.tokens.first() {
+            Some(token) => Err(Error::parse(DAY, self.source, token, "end of line")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A set of program positions, one bit each.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Visited {
+    words: Vec<u64>,
+}
+impl Visited {
+    pub fn new() -> Visited {
+        Visited::default()
+    }
+    pub fn contains(&self, pc: usize) -> bool {
+        self.words
+            .get(pc / 64)
+            .is_some_and(|word| word & (1 << (pc % 64)) != 0)
+    }
+    /// Adds `pc`, returning whether it was new.
+    pub fn insert(&mut self, pc: usize) -> bool {
+        if pc / 64 >= self.words.len() {
+            self.words.resize(pc / 64 + 1, 0);
+        }
+        let word = &mut self.words[pc / 64];
+        let new = *word & (1 << (pc % 64)) == 0;
+        *word |= 1 << (pc % 64);
+        new
+    }
+    pub fn remove(&mut self, pc: usize) {
+        if let Some(word) = self.words.get_mut(pc / 64) {
+            *word &= !(1 << (pc % 64));
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+}
+impl FromIterator<usize> for Visited {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Visited {
+        let mut visited = Visited::new();
+        for pc in iter {
+            visited.insert(pc);
+        }
+        visited
+    }
+}
+
+/// Executes a program. Boot code stops as soon as any instruction would run a
+/// second time, so the interpreter remembers every instruction it has
+/// visited.
 #[derive(Debug, Clone)]
-struct Interpreter {
-    program: Vec<Instruction>,
-    acc: i32,
-    pos: i32,
-    visited: Vec<i32>,
+pub struct Interpreter {
+    pub program: Vec<Instruction>,
+    pub registers: [i32; REGISTERS],
+    pub pos: i32,
+    pub visited: Visited,
+    /// Values still to be read by `in`.
+    pub input: VecDeque<i32>,
+    /// Values written by `out`.
+    pub output: Vec<i32>,
+    /// Whether to stop with [`Status::Loop`] before running an instruction a
+    /// second time. Only boot code must loop when it does; programs with
+    /// conditional jumps may well not.
+    pub halt_on_repeat: bool,
 }
 impl Interpreter {
-    fn new(program: Vec<Instruction>) -> Interpreter {
+    pub fn new(program: Vec<Instruction>) -> Interpreter {
         Interpreter {
             program,
-            acc: 0,
+            registers: [0; REGISTERS],
             pos: 0,
-            visited: Vec::new(),
+            visited: Visited::new(),
+            input: VecDeque::new(),
+            output: Vec::new(),
+            halt_on_repeat: true,
+        }
+    }
+    /// An interpreter that never stops just for repeating an instruction.
+    pub fn unchecked(program: Vec<Instruction>) -> Interpreter {
+        Interpreter {
+            halt_on_repeat: false,
+            ..Interpreter::new(program)
         }
     }
-    fn step(&mut self) -> Option<Status> {
-        if self.visited.contains(&self.pos) {
+    pub fn with_input<I: IntoIterator<Item = i32>>(mut self, input: I) -> Interpreter {
+        self.input.extend(input);
+        self
+    }
+    pub fn acc(&self) -> i32 {
+        self.registers[ACC]
+    }
+
+    /// Executes one instruction, returning a status once the program halts.
+    pub fn step(&mut self) -> Option<Status> {
+        if self.halt_on_repeat && self.pos >= 0 && !self.visited.insert(self.pos as usize) {
             return Some(Status::Loop);
         }
-        self.visited.push(self.pos);
 
         let pos: usize = self.pos as usize;
         if pos == self.program.len() {
@@ -98,71 +447,994 @@ impl Interpreter {
         if pos > self.program.len() {
             return Some(Status::Error);
         }
-        let instruction = &self.program[pos];
-        match instruction.opcode {
-            OpCode::Jump => {
-                self.pos += instruction.arg;
+        let value = |registers: &[i32; REGISTERS], x: Operand| match x {
+            Operand::Reg(r) => registers[r],
+            Operand::Imm(n) => n,
+        };
+        let mut next = self.pos + 1;
+        match self.program[pos] {
+            Instruction::Nop(_) => {}
+            Instruction::Acc(n) => {
+                self.registers[ACC] = self.registers[ACC].wrapping_add(n);
             }
-            OpCode::Nop => {
-                self.pos += 1;
+            Instruction::Jump(n) => next = self.pos + n,
+            Instruction::Set(r, x) => self.registers[r] = value(&self.registers, x),
+            Instruction::Arith(op, r, x) => {
+                match op.apply(self.registers[r], value(&self.registers, x)) {
+                    Some(result) => self.registers[r] = result,
+                    None => return Some(Status::Error),
+                }
             }
-            OpCode::Acc => {
-                self.acc += instruction.arg;
-                self.pos += 1;
+            Instruction::Branch(cond, r, n) => {
+                if cond.holds(self.registers[r]) {
+                    next = self.pos + n;
+                }
             }
+            Instruction::In(r) => match self.input.pop_front() {
+                Some(x) => self.registers[r] = x,
+                None => {
+                    // Let the instruction run again once there is input.
+                    self.visited.remove(pos);
+                    return Some(Status::Waiting);
+                }
+            },
+            Instruction::Out(x) => self.output.push(value(&self.registers, x)),
+            Instruction::Halt => next = self.program.len() as i32,
         }
+        self.pos = next;
         None
     }
-    fn drive(&mut self) -> Status {
+    /// Steps until the program halts, which without
+    /// [`Interpreter::halt_on_repeat`] it might never do.
+    pub fn drive(&mut self) -> Status {
         loop {
             if let Some(status) = self.step() {
                 return status;
             }
         }
     }
+    /// Steps until the program halts or `limit` instructions have run.
+    pub fn drive_for(&mut self, limit: usize) -> Option<Status> {
+        (0..limit).find_map(|_| self.step())
+    }
 }
-#[derive(Debug, Eq, PartialEq)]
-enum Status {
+/// Why an [`Interpreter`] stopped.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Status {
     Complete,
+    /// Jumped outside the program, or divided by zero.
     Error,
     Loop,
+    /// Ran `in` with no input left; add some and keep stepping.
+    Waiting,
+}
+
+/// Where a [`Debugger`] should pause.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Breakpoint {
+    /// Before running the instruction at this position.
+    Pc(i32),
+    /// After an instruction changes the register to this value.
+    Register(Register, i32),
+}
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Pc(pc) => write!(f, "pc {}", pc),
+            Breakpoint::Register(r, value) => write!(f, "{} {}", REGISTER_NAMES[*r], value),
+        }
+    }
 }
 
-fn final_acc(mut interpreter: Interpreter) -> Option<i32> {
+/// Why a [`Debugger`] stopped running.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Stop {
+    Halted(Status),
+    Breakpoint(Breakpoint),
+    /// Reached the position given to [`Debugger::run_to`].
+    Reached,
+    /// Ran [`Debugger::limit`] instructions without stopping.
+    Limit,
+}
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Halted(Status::Complete) => f.write_str("program complete"),
+            Stop::Halted(Status::Error) => f.write_str("program failed"),
+            Stop::Halted(Status::Loop) => f.write_str("instruction would run twice"),
+            Stop::Halted(Status::Waiting) => f.write_str("waiting for input"),
+            Stop::Breakpoint(breakpoint) => write!(f, "breakpoint: {}", breakpoint),
+            Stop::Reached => f.write_str("reached"),
+            Stop::Limit => f.write_str("step limit reached"),
+        }
+    }
+}
+
+// Enough to undo one step of an interpreter.
+#[derive(Debug, Clone)]
+struct Undo {
+    pos: i32,
+    registers: [i32; REGISTERS],
+    // Whether the step added `pos` to the visited set.
+    marked: bool,
+    output: usize,
+    // The input value the step consumed, if any.
+    read: Option<i32>,
+}
+
+/// Runs an [`Interpreter`] under control: stepping forwards and backwards,
+/// pausing at breakpoints and keeping a trace of what ran.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    pub interpreter: Interpreter,
+    pub breakpoints: Vec<Breakpoint>,
+    /// The most instructions one [`Debugger::run`] may execute.
+    pub limit: usize,
+    history: Vec<Undo>,
+}
+impl Debugger {
+    pub fn new(interpreter: Interpreter) -> Debugger {
+        Debugger {
+            interpreter,
+            breakpoints: Vec::new(),
+            limit: 1_000_000,
+            history: Vec::new(),
+        }
+    }
+
+    /// Executes one instruction, returning a status instead if the program
+    /// has halted. A halted program is left as it was, so stepping again
+    /// halts again.
+    pub fn step(&mut self) -> Option<Status> {
+        let it = &self.interpreter;
+        let mut undo = Undo {
+            pos: it.pos,
+            registers: it.registers,
+            marked: it.halt_on_repeat && it.pos >= 0 && !it.visited.contains(it.pos as usize),
+            output: it.output.len(),
+            read: it.input.front().copied(),
+        };
+        let input = it.input.len();
+        let status = self.interpreter.step();
+        if self.interpreter.input.len() == input {
+            undo.read = None;
+        }
+        match status {
+            Some(status) => {
+                self.restore(undo);
+                Some(status)
+            }
+            None => {
+                self.history.push(undo);
+                None
+            }
+        }
+    }
+    /// Undoes the last step; `false` if there is none.
+    pub fn back(&mut self) -> bool {
+        match self.history.pop() {
+            Some(undo) => {
+                self.restore(undo);
+                true
+            }
+            None => false,
+        }
+    }
+    fn restore(&mut self, undo: Undo) {
+        let it = &mut self.interpreter;
+        it.pos = undo.pos;
+        it.registers = undo.registers;
+        if undo.marked {
+            it.visited.remove(undo.pos as usize);
+        }
+        it.output.truncate(undo.output);
+        if let Some(value) = undo.read {
+            it.input.push_front(value);
+        }
+    }
+
+    /// Steps until the program halts or hits a breakpoint.
+    pub fn run(&mut self) -> Stop {
+        self.run_until(|_| false)
+    }
+    /// Steps until reaching position `pc`, the program halts or it hits a
+    /// breakpoint.
+    pub fn run_to(&mut self, pc: i32) -> Stop {
+        self.run_until(|it| it.pos == pc)
+    }
+    fn run_until<F: Fn(&Interpreter) -> bool>(&mut self, target: F) -> Stop {
+        for _ in 0..self.limit {
+            if let Some(status) = self.step() {
+                return Stop::Halted(status);
+            }
+            if target(&self.interpreter) {
+                return Stop::Reached;
+            }
+            if let Some(breakpoint) = self.hit() {
+                return Stop::Breakpoint(breakpoint);
+            }
+        }
+        Stop::Limit
+    }
+    // The first breakpoint the last step triggered.
+    fn hit(&self) -> Option<Breakpoint> {
+        let it = &self.interpreter;
+        let before = self.history.last()?;
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| match **breakpoint {
+                Breakpoint::Pc(pc) => it.pos == pc,
+                Breakpoint::Register(r, value) => {
+                    it.registers[r] == value && before.registers[r] != value
+                }
+            })
+            .copied()
+    }
+
+    /// The positions of every instruction run so far, oldest first.
+    pub fn trace(&self) -> impl Iterator<Item = i32> + '_ {
+        self.history.iter().map(|undo| undo.pos)
+    }
+    /// `pc: instruction`, `pc: end` just past the program, or
+    /// `pc: outside` anywhere else.
+    pub fn listing(&self, pc: i32) -> String {
+        let program = &self.interpreter.program;
+        if pc >= 0 && (pc as usize) < program.len() {
+            format!("{:4}: {}", pc, program[pc as usize])
+        } else if pc as usize == program.len() {
+            format!("{:4}: end", pc)
+        } else {
+            format!("{:4}: outside", pc)
+        }
+    }
+    /// The position and every register, e.g. `pc=3 acc=5 r1=0 ...`.
+    pub fn registers(&self) -> String {
+        let it = &self.interpreter;
+        let mut line = format!("pc={}", it.pos);
+        for (name, value) in REGISTER_NAMES.iter().zip(it.registers.iter()) {
+            line += &format!(" {}={}", name, value);
+        }
+        line
+    }
+
+    /// Runs one debugger command, returning what to show. See
+    /// [`DEBUGGER_HELP`] for the commands.
+    pub fn command(&mut self, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let count = |i: usize| -> Result<usize, String> {
+            words.get(i).map_or(Ok(1), |n| {
+                n.parse()
+                    .map_err(|_| format!("invalid count: {} (expected a number)", n))
+            })
+        };
+        let number = |i: usize| -> Result<i32, String> {
+            let word = words.get(i).ok_or("missing number")?;
+            word.parse()
+                .map_err(|_| format!("invalid number: {} (expected e.g. -4)", word))
+        };
+        let here = |debugger: &Debugger| debugger.listing(debugger.interpreter.pos);
+        match words.first().copied().unwrap_or("step") {
+            "s" | "step" => {
+                for _ in 0..count(1)? {
+                    if let Some(status) = self.step() {
+                        return Ok(format!("{}\n{}", Stop::Halted(status), here(self)));
+                    }
+                }
+                Ok(here(self))
+            }
+            "b" | "back" => {
+                for _ in 0..count(1)? {
+                    if !self.back() {
+                        return Ok(format!("at the start of history\n{}", here(self)));
+                    }
+                }
+                Ok(here(self))
+            }
+            "c" | "continue" => {
+                let stop = self.run();
+                Ok(format!("{}\n{}", stop, here(self)))
+            }
+            "u" | "until" => {
+                let stop = self.run_to(number(1)?);
+                Ok(format!("{}\n{}", stop, here(self)))
+            }
+            "break" => {
+                let breakpoint = match words.get(1).copied() {
+                    Some("pc") => Breakpoint::Pc(number(2)?),
+                    Some(name) => match REGISTER_NAMES.iter().position(|&r| r == name) {
+                        Some(r) => Breakpoint::Register(r, number(2)?),
+                        None => {
+                            return Err(format!(
+                                "invalid breakpoint: {} (expected pc or a register)",
+                                name
+                            ))
+                        }
+                    },
+                    None => return Err("missing breakpoint".to_owned()),
+                };
+                self.breakpoints.push(breakpoint);
+                Ok(format!(
+                    "breakpoint {}: {}",
+                    self.breakpoints.len(),
+                    breakpoint
+                ))
+            }
+            "delete" => {
+                if words.len() < 2 {
+                    return Err("missing breakpoint number (usage: delete <i>)".to_owned());
+                }
+                let n = count(1)?;
+                if n == 0 || n > self.breakpoints.len() {
+                    return Err(format!("no breakpoint {}", n));
+                }
+                let breakpoint = self.breakpoints.remove(n - 1);
+                Ok(format!("deleted {}", breakpoint))
+            }
+            "breakpoints" => Ok(self
+                .breakpoints
+                .iter()
+                .enumerate()
+                .map(|(i, breakpoint)| format!("breakpoint {}: {}", i + 1, breakpoint))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            "r" | "registers" => Ok(self.registers()),
+            "trace" => {
+                let n = words.get(1).map_or(Ok(10), |_| count(1))?;
+                let skip = self.history.len().saturating_sub(n);
+                Ok(self
+                    .trace()
+                    .skip(skip)
+                    .map(|pc| self.listing(pc))
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            "l" | "list" => {
+                let pos = self.interpreter.pos;
+                let end = self.interpreter.program.len() as i32;
+                Ok((pos - 3..=pos + 3)
+                    .filter(|pc| (0..=end).contains(pc))
+                    .map(|pc| {
+                        let marker = if pc == pos { "=>" } else { "  " };
+                        format!("{}{}", marker, self.listing(pc))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            "input" => {
+                for i in 1..words.len() {
+                    let value = number(i)?;
+                    self.interpreter.input.push_back(value);
+                }
+                Ok(format!("{} value(s) waiting", self.interpreter.input.len()))
+            }
+            "output" => Ok(self
+                .interpreter
+                .output
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")),
+            "loops" => {
+                let on = match words.get(1).copied() {
+                    Some("on") => true,
+                    Some("off") => false,
+                    _ => return Err("expected `loops on` or `loops off`".to_owned()),
+                };
+                self.interpreter.halt_on_repeat = on;
+                Ok(format!("loop check {}", if on { "on" } else { "off" }))
+            }
+            "h" | "help" => Ok(DEBUGGER_HELP.to_owned()),
+            command => Err(format!("unknown command: {} (try `help`)", command)),
+        }
+    }
+    /// Reads commands from `input` until it ends or says `quit`, writing each
+    /// result to `output`.
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        writeln!(output, "{}", self.listing(self.interpreter.pos))?;
+        write!(output, "(debug) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            match line.trim() {
+                "q" | "quit" => break,
+                command => match self.command(command) {
+                    Ok(text) if text.is_empty() => {}
+                    Ok(text) => writeln!(output, "{}", text)?,
+                    Err(err) => writeln!(output, "error: {}", err)?,
+                },
+            }
+            write!(output, "(debug) ")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+/// The commands [`Debugger::command`] understands.
+pub const DEBUGGER_HELP: &str = "\
+step [n]           run n instructions (default 1; also an empty line)
+back [n]           undo n instructions
+continue           run until a breakpoint or the program halts
+until <pc>         run until reaching position pc
+break pc <pc>      pause before running the instruction at pc
+break <reg> <n>    pause when an instruction sets reg to n
+delete <i>         remove breakpoint i
+breakpoints        list breakpoints
+registers          show pc and every register
+trace [n]          show the last n instructions run (default 10)
+list               show the instructions around pc
+input <n>...       queue values for `in`
+output             show values written by `out`
+loops on|off       whether running an instruction twice halts the program
+quit               leave the debugger";
+
+/// The accumulator just before any instruction would run a second time.
+pub fn final_acc(mut interpreter: Interpreter) -> Option<i32> {
     match interpreter.drive() {
-        Status::Complete | Status::Error => None,
-        Status::Loop => Some(interpreter.acc),
+        Status::Complete | Status::Error | Status::Waiting => None,
+        Status::Loop => Some(interpreter.acc()),
+    }
+}
+
+/// The accumulator after a terminating run, having swapped exactly one `nop`
+/// and `jmp` (see [`find_corruption`]).
+pub fn final_fixed_acc(interpreter: Interpreter) -> Option<i32> {
+    let i = find_corruption(&interpreter.program)?;
+    let mut fixed = interpreter;
+    fixed.program[i] = fixed.program[i].swapped()?;
+    match fixed.drive() {
+        Status::Complete => Some(fixed.acc()),
+        _ => None,
+    }
+}
+
+/// The position of the `nop` or `jmp` whose swap makes looping boot code
+/// terminate, in time linear in the program's length; `None` if there is
+/// none, or the program is not boot code.
+///
+/// For boot code that terminates already, this is the first instruction it
+/// runs whose swap still lets it terminate, while [`swap_fixes`] reports
+/// nothing to fix.
+pub fn find_corruption(program: &[Instruction]) -> Option<usize> {
+    let (swaps, _) = terminating_swaps(program)?;
+    swaps.first().copied()
+}
+
+/// Every `nop` or `jmp` whose swap alone makes looping boot code terminate;
+/// `None` if the program is not boot code.
+///
+/// Working backwards from the end of the program finds every position that
+/// already runs to completion. Swapping an instruction the program never
+/// runs changes nothing, and swapping one it does run fixes it exactly when
+/// the swap leads to one of those positions without coming back to the
+/// swapped instruction.
+pub fn swap_fixes(program: &[Instruction]) -> Option<Vec<usize>> {
+    let (swaps, terminates) = terminating_swaps(program)?;
+    if terminates {
+        return Some(Vec::new());
     }
+    Some(swaps)
 }
 
-fn final_fixed_acc(interpreter: Interpreter) -> Option<i32> {
-    for i in 0..interpreter.program.len() {
-        let mut fixed = interpreter.clone();
-        match interpreter.program[i].opcode {
-            OpCode::Acc => continue,
-            OpCode::Nop => fixed.program[i].opcode = OpCode::Jump,
-            OpCode::Jump => fixed.program[i].opcode = OpCode::Nop,
+// Every `nop` or `jmp` boot code runs whose swap makes it terminate, and
+// whether it terminates already; `None` if the program is not boot code.
+fn terminating_swaps(program: &[Instruction]) -> Option<(Vec<usize>, bool)> {
+    let end = program.len();
+    let successors: Vec<i32> = program
+        .iter()
+        .enumerate()
+        .map(|(pc, &instruction)| boot_successor(pc as i32, instruction))
+        .collect::<Option<_>>()?;
+
+    // The unswapped run, and the step at which it runs each position.
+    let mut path = Vec::new();
+    let mut step = vec![None; end];
+    let mut pc = 0;
+    while (0..end as i32).contains(&pc) && step[pc as usize].is_none() {
+        step[pc as usize] = Some(path.len());
+        path.push(pc as usize);
+        pc = successors[pc as usize];
+    }
+
+    // For each position that runs to completion, the earliest step of the
+    // run it passes through on the way, or `path.len()` if none. A swap at
+    // step `i` leading there terminates exactly when that is after `i`:
+    // otherwise it comes back to the swapped instruction.
+    let mut predecessors = vec![Vec::new(); end + 1];
+    for (pc, &next) in successors.iter().enumerate() {
+        if (0..=end as i32).contains(&next) {
+            predecessors[next as usize].push(pc);
+        }
+    }
+    let mut rejoins = vec![None; end + 1];
+    rejoins[end] = Some(path.len());
+    let mut queue = vec![end];
+    while let Some(next) = queue.pop() {
+        for &pc in &predecessors[next] {
+            if rejoins[pc].is_none() {
+                rejoins[pc] = step[pc].or(rejoins[next]);
+                queue.push(pc);
+            }
+        }
+    }
+
+    let mut fixes = Vec::new();
+    for (i, &pc) in path.iter().enumerate() {
+        if let Some(swapped) = program[pc].swapped() {
+            let next = boot_successor(pc as i32, swapped)?;
+            if (0..=end as i32).contains(&next) && rejoins[next as usize].is_some_and(|s| s > i) {
+                fixes.push(pc);
+            }
+        }
+    }
+    Some((fixes, pc == end as i32))
+}
+
+// Where boot code goes after running `instruction` at `pc`; `None` for
+// instructions that boot code does not have.
+fn boot_successor(pc: i32, instruction: Instruction) -> Option<i32> {
+    match instruction {
+        Instruction::Nop(_) | Instruction::Acc(_) => Some(pc + 1),
+        Instruction::Jump(n) => Some(pc + n),
+        _ => None,
+    }
+}
+
+/// A place control can reach in a [`Cfg`].
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, PartialOrd, Ord)]
+pub enum Node {
+    /// The instruction at this position.
+    Pc(usize),
+    /// Just past the last instruction, where the program completes.
+    End,
+    /// Anywhere else, where the program fails.
+    Outside,
+}
+
+/// The control-flow graph of a program, built without running it.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Cfg {
+    /// Where control can go after each instruction. Conditional jumps have
+    /// two successors, as do divisions that may divide by zero, which fail
+    /// by going [`Node::Outside`]; everything else has one.
+    pub successors: Vec<Vec<Node>>,
+    /// The basic blocks, in order: runs of instructions that are only ever
+    /// entered at the top and left at the bottom.
+    pub blocks: Vec<Range<usize>>,
+}
+impl Cfg {
+    pub fn new(program: &[Instruction]) -> Cfg {
+        let node = |target: i32| match target {
+            t if t >= 0 && (t as usize) < program.len() => Node::Pc(t as usize),
+            t if t as usize == program.len() && t >= 0 => Node::End,
+            _ => Node::Outside,
         };
-        if let Status::Complete = fixed.drive() {
-            return Some(fixed.acc);
+        let successors: Vec<Vec<Node>> = program
+            .iter()
+            .enumerate()
+            .map(|(pc, instruction)| {
+                let pc = pc as i32;
+                match *instruction {
+                    Instruction::Jump(n) => vec![node(pc + n)],
+                    Instruction::Branch(_, _, 1) => vec![node(pc + 1)],
+                    Instruction::Branch(_, _, n) => vec![node(pc + 1), node(pc + n)],
+                    Instruction::Halt => vec![Node::End],
+                    Instruction::Arith(ArithOp::Div | ArithOp::Mod, _, Operand::Imm(0)) => {
+                        vec![Node::Outside]
+                    }
+                    Instruction::Arith(ArithOp::Div | ArithOp::Mod, _, Operand::Reg(_)) => {
+                        vec![node(pc + 1), Node::Outside]
+                    }
+                    _ => vec![node(pc + 1)],
+                }
+            })
+            .collect();
+
+        // A block starts at the entry, at every jump target and after every
+        // instruction that can go anywhere but the next one.
+        let mut leaders = Visited::new();
+        if !program.is_empty() {
+            leaders.insert(0);
         }
+        for (pc, next) in successors.iter().enumerate() {
+            if next != &[Node::Pc(pc + 1)] {
+                for node in next {
+                    if let Node::Pc(target) = *node {
+                        leaders.insert(target);
+                    }
+                }
+                if pc + 1 < program.len() {
+                    leaders.insert(pc + 1);
+                }
+            }
+        }
+        let starts: Vec<usize> = (0..program.len())
+            .filter(|&pc| leaders.contains(pc))
+            .collect();
+        let blocks = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| start..starts.get(i + 1).copied().unwrap_or(program.len()))
+            .collect();
+        Cfg { successors, blocks }
+    }
+    /// The index of the block holding the instruction at `pc`.
+    pub fn block_of(&self, pc: usize) -> usize {
+        self.blocks.partition_point(|block| block.end <= pc)
+    }
+
+    /// Every instruction some path from the entry runs.
+    pub fn reachable(&self) -> Visited {
+        let mut reached = Visited::new();
+        let mut queue = Vec::new();
+        if !self.successors.is_empty() && reached.insert(0) {
+            queue.push(0);
+        }
+        while let Some(pc) = queue.pop() {
+            for node in &self.successors[pc] {
+                if let Node::Pc(next) = *node {
+                    if reached.insert(next) {
+                        queue.push(next);
+                    }
+                }
+            }
+        }
+        reached
+    }
+    /// Every instruction with some path to `target`.
+    pub fn reaching(&self, target: Node) -> Visited {
+        let mut predecessors = vec![Vec::new(); self.successors.len()];
+        let mut queue = Vec::new();
+        let mut reached = Visited::new();
+        for (pc, next) in self.successors.iter().enumerate() {
+            for &node in next {
+                if node == target {
+                    if reached.insert(pc) {
+                        queue.push(pc);
+                    }
+                } else if let Node::Pc(next) = node {
+                    predecessors[next].push(pc);
+                }
+            }
+        }
+        while let Some(pc) = queue.pop() {
+            for &predecessor in &predecessors[pc] {
+                if reached.insert(predecessor) {
+                    queue.push(predecessor);
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// What can be learnt about a program without running it.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Analysis {
+    pub program: Vec<Instruction>,
+    pub cfg: Cfg,
+    /// Instructions no path from the entry runs.
+    pub unreachable: Vec<usize>,
+    /// Instructions that may run, after which the program can neither
+    /// complete nor fail: it is stuck in an infinite loop.
+    pub endless: Vec<usize>,
+    /// For boot code, the instructions whose swap alone makes the program
+    /// terminate (see [`swap_fixes`]).
+    pub fixes: Vec<usize>,
+}
+impl Analysis {
+    pub fn new(program: &[Instruction]) -> Analysis {
+        let cfg = Cfg::new(program);
+        let reachable = cfg.reachable();
+        let (completes, fails) = (cfg.reaching(Node::End), cfg.reaching(Node::Outside));
+        let unreachable = (0..program.len())
+            .filter(|&pc| !reachable.contains(pc))
+            .collect();
+        let endless = (0..program.len())
+            .filter(|&pc| reachable.contains(pc) && !completes.contains(pc) && !fails.contains(pc))
+            .collect();
+        Analysis {
+            program: program.to_vec(),
+            cfg,
+            unreachable,
+            endless,
+            fixes: swap_fixes(program).unwrap_or_default(),
+        }
+    }
+    /// Whether the program is sure to loop forever.
+    pub fn never_halts(&self) -> bool {
+        self.endless.first() == Some(&0)
+    }
+
+    /// The control-flow graph in Graphviz's DOT language, one box per basic
+    /// block. Unreachable blocks are dashed, endless ones red, and
+    /// instructions whose swap fixes the program are marked `fix`.
+    pub fn dot(&self) -> String {
+        let mut dot =
+            String::from("digraph program {\n    node [shape=box, fontname=monospace];\n");
+        let unreachable: Visited = self.unreachable.iter().copied().collect();
+        let endless: Visited = self.endless.iter().copied().collect();
+        let fixes: Visited = self.fixes.iter().copied().collect();
+        for (i, block) in self.cfg.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for pc in block.clone() {
+                let fix = if fixes.contains(pc) { "  (fix)" } else { "" };
+                label += &format!("{}: {}{}\\l", pc, self.program[pc], fix);
+            }
+            let style = if unreachable.contains(block.start) {
+                ", style=dashed"
+            } else if endless.contains(block.start) {
+                ", color=red"
+            } else {
+                ""
+            };
+            dot += &format!("    b{} [label=\"{}\"{}];\n", i, label, style);
+        }
+        dot += "    end [shape=doublecircle];\n";
+        if self
+            .cfg
+            .successors
+            .iter()
+            .flatten()
+            .any(|&node| node == Node::Outside)
+        {
+            dot += "    outside [shape=octagon];\n";
+        }
+        if !self.program.is_empty() {
+            dot += "    start [shape=point];\n    start -> b0;\n";
+            for (i, block) in self.cfg.blocks.iter().enumerate() {
+                let tail = block.end - 1;
+                for node in &self.cfg.successors[tail] {
+                    let target = match *node {
+                        Node::Pc(pc) => format!("b{}", self.cfg.block_of(pc)),
+                        Node::End => "end".to_owned(),
+                        Node::Outside => "outside".to_owned(),
+                    };
+                    dot += &format!("    b{} -> {};\n", i, target);
+                }
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+impl fmt::Display for Analysis {
+    /// A summary listing the blocks and each finding.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |pcs: &[usize]| match pcs {
+            [] => "none".to_owned(),
+            pcs => pcs
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let blocks: Vec<String> = self
+            .cfg
+            .blocks
+            .iter()
+            .map(|block| match block.len() {
+                1 => block.start.to_string(),
+                _ => format!("{}-{}", block.start, block.end - 1),
+            })
+            .collect();
+        writeln!(f, "blocks: {}", blocks.join(", "))?;
+        writeln!(f, "unreachable: {}", list(&self.unreachable))?;
+        writeln!(f, "endless: {}", list(&self.endless))?;
+        writeln!(f, "fixes: {}", list(&self.fixes))
+    }
+}
+
+/// A change to one instruction of a program, by its original position.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Edit {
+    /// Turns a `nop` into a `jmp` or the other way round.
+    Swap(usize),
+    /// Changes an `acc`'s argument.
+    SetAcc(usize, i32),
+    Delete(usize),
+    /// Inserts an instruction before this position, or at the end.
+    Insert(usize, Instruction),
+}
+impl Edit {
+    pub fn pos(self) -> usize {
+        match self {
+            Edit::Swap(pc) | Edit::SetAcc(pc, _) | Edit::Delete(pc) | Edit::Insert(pc, _) => pc,
+        }
+    }
+}
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Swap(pc) => write!(f, "{}: swap", pc),
+            Edit::SetAcc(pc, n) => write!(f, "{}: acc {:+}", pc, n),
+            Edit::Delete(pc) => write!(f, "{}: delete", pc),
+            Edit::Insert(pc, instruction) => write!(f, "{}: insert {}", pc, instruction),
+        }
+    }
+}
+
+/// `program` with `edits` made, at most one per position. Jump offsets are
+/// kept as written, as if editing the source.
+pub fn apply_edits(program: &[Instruction], edits: &[Edit]) -> Vec<Instruction> {
+    let mut patched = Vec::with_capacity(program.len() + edits.len());
+    for pc in 0..=program.len() {
+        let edit = edits.iter().find(|edit| edit.pos() == pc).copied();
+        if let Some(Edit::Insert(_, instruction)) = edit {
+            patched.push(instruction);
+        }
+        if let Some(&instruction) = program.get(pc) {
+            match edit {
+                Some(Edit::Swap(_)) => patched.push(instruction.swapped().unwrap_or(instruction)),
+                Some(Edit::SetAcc(_, n)) => patched.push(Instruction::Acc(n)),
+                Some(Edit::Delete(_)) => {}
+                _ => patched.push(instruction),
+            }
+        }
+    }
+    patched
+}
+
+/// What makes one fix better than another.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Objective {
+    /// Any fix that terminates.
+    Terminate,
+    /// Fixes that terminate with the largest accumulator.
+    MaxAcc,
+    /// Fixes that terminate with the fewest edits.
+    MinEdits,
+}
+
+/// A set of edits that makes a program terminate.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Fix {
+    pub edits: Vec<Edit>,
+    /// The accumulator once the fixed program completes.
+    pub acc: i32,
+}
+impl Fix {
+    // Whether every one of this fix's edits is among `edits`.
+    fn within(&self, edits: &[Edit]) -> bool {
+        self.edits.iter().all(|edit| edits.contains(edit))
+    }
+}
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let edits: Vec<String> = self.edits.iter().map(Edit::to_string).collect();
+        write!(f, "{} (acc {})", edits.join(", "), self.acc)
+    }
+}
+
+/// A search for the edits that make boot code terminate. The default matches
+/// the puzzle: a single `nop`/`jmp` swap.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Repair {
+    /// The most edits in one fix.
+    pub max_edits: usize,
+    pub swaps: bool,
+    /// Arguments to try in place of each `acc`'s.
+    pub acc_values: Vec<i32>,
+    pub deletions: bool,
+    /// Instructions to try inserting at each position.
+    pub insertions: Vec<Instruction>,
+    pub objective: Objective,
+}
+impl Default for Repair {
+    fn default() -> Self {
+        Repair {
+            max_edits: 1,
+            swaps: true,
+            acc_values: Vec::new(),
+            deletions: false,
+            insertions: Vec::new(),
+            objective: Objective::Terminate,
+        }
+    }
+}
+impl Repair {
+    /// Every single edit the search may make to `program`.
+    pub fn candidates(&self, program: &[Instruction]) -> Vec<Edit> {
+        let mut edits = Vec::new();
+        for pc in 0..=program.len() {
+            edits.extend(self.insertions.iter().map(|&i| Edit::Insert(pc, i)));
+            let instruction = match program.get(pc) {
+                Some(&instruction) => instruction,
+                None => break,
+            };
+            if self.swaps && instruction.swapped().is_some() {
+                edits.push(Edit::Swap(pc));
+            }
+            if let Instruction::Acc(arg) = instruction {
+                let values = self.acc_values.iter().filter(|&&n| n != arg);
+                edits.extend(values.map(|&n| Edit::SetAcc(pc, n)));
+            }
+            if self.deletions {
+                edits.push(Edit::Delete(pc));
+            }
+        }
+        edits
+    }
+
+    /// Every fix of up to [`Repair::max_edits`] edits that is best for the
+    /// objective and minimal, in that no smaller set of its edits does as
+    /// well. Fixes come in order of size and then position.
+    ///
+    /// This tries every combination of candidate edits, so it is only quick
+    /// for a few edits.
+    pub fn search(&self, program: &[Instruction]) -> Vec<Fix> {
+        // A bigger accumulator can justify extra edits; otherwise any
+        // superset of a fix is no better than it.
+        let prune = self.objective != Objective::MaxAcc;
+        let candidates = self.candidates(program);
+        let mut fixes: Vec<Fix> = Vec::new();
+        for size in 0..=self.max_edits {
+            if self.objective == Objective::MinEdits && !fixes.is_empty() {
+                break;
+            }
+            for edits in candidates.iter().copied().combinations(size) {
+                let one_per_pos = edits.iter().map(|edit| edit.pos()).all_unique();
+                if !one_per_pos || prune && fixes.iter().any(|fix| fix.within(&edits)) {
+                    continue;
+                }
+                let mut interpreter = Interpreter::new(apply_edits(program, &edits));
+                if interpreter.drive() == Status::Complete {
+                    let acc = interpreter.acc();
+                    fixes.push(Fix { edits, acc });
+                }
+            }
+        }
+        if self.objective == Objective::MaxAcc {
+            if let Some(best) = fixes.iter().map(|fix| fix.acc).max() {
+                fixes.retain(|fix| fix.acc == best);
+            }
+            let best = fixes.clone();
+            fixes.retain(|fix| {
+                let smaller = best
+                    .iter()
+                    .filter(|other| other.edits.len() < fix.edits.len());
+                !smaller.into_iter().any(|other| other.within(&fix.edits))
+            });
+        }
+        fixes
+    }
+}
+
+/// [`Solution`] for day 8.
+pub struct Day08;
+impl Solution for Day08 {
+    type Input = Vec<Instruction>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_instructions(input)
+    }
+    fn part1(input: &Self::Input) -> Option<i32> {
+        final_acc(Interpreter::new(input.clone()))
+    }
+    fn part2(input: &Self::Input) -> Option<i32> {
+        final_fixed_acc(Interpreter::new(input.clone()))
     }
-    None
 }
 
 #[cfg(test)]
 mod test {
     use super::{
-        final_acc, final_fixed_acc, instruction_parser, parse_instructions, Instruction,
-        Interpreter,
+        apply_edits, assemble, final_acc, final_fixed_acc, find_corruption, instruction_parser,
+        parse_instructions, swap_fixes, Analysis, ArithOp, Breakpoint, Condition, Debugger, Edit,
+        Fix, Instruction, Interpreter, Node, Objective, Operand, Repair, Status, Stop, Visited,
+        ACC,
     };
+    use crate::error::Error;
+
     #[test]
     fn parser_one() {
-        assert_eq!(instruction_parser("nop +0").unwrap().1, Instruction::nop(0));
-        assert_eq!(instruction_parser("acc +1").unwrap().1, Instruction::acc(1));
+        assert_eq!(instruction_parser("nop +0").unwrap().1, Instruction::Nop(0));
+        assert_eq!(instruction_parser("acc +1").unwrap().1, Instruction::Acc(1));
         assert_eq!(
             instruction_parser("jmp -4").unwrap().1,
-            Instruction::jmp(-4)
+            Instruction::Jump(-4)
         );
     }
 
@@ -180,22 +1452,36 @@ mod test {
     #[test]
     fn parser_list() {
         let parsed = parse_instructions(SMALL).unwrap();
-        assert_eq!(parsed[0], Instruction::nop(0));
-        assert_eq!(parsed[1], Instruction::acc(1));
-        assert_eq!(parsed[2], Instruction::jmp(4));
+        assert_eq!(parsed[0], Instruction::Nop(0));
+        assert_eq!(parsed[1], Instruction::Acc(1));
+        assert_eq!(parsed[2], Instruction::Jump(4));
     }
 
     #[test]
-    fn small1() {
-        let interpreter = Interpreter::new(parse_instructions(SMALL).unwrap());
-        assert_eq!(final_acc(interpreter).unwrap(), 5);
+    fn parser_errors() {
+        assert_eq!(
+            parse_instructions("nop +0\nacc +1\njmp x4\n").unwrap_err(),
+            Error::Parse {
+                day: 8,
+                line: 3,
+                column: 5,
+                expected: "an instruction like `acc +1`".to_owned(),
+                found: "`x4`".to_owned(),
+            }
+        );
+        assert_eq!(
+            parse_instructions("nop +0\nmul +1")
+                .unwrap_err()
+                .to_string(),
+            "day 8, line 2, column 1: expected an instruction like `acc +1`, found `mul`"
+        );
+        assert!(parse_instructions("  ").is_err());
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day08.input").unwrap();
-        let interpreter = Interpreter::new(parse_instructions(&raw).unwrap());
-        assert_eq!(final_acc(interpreter).unwrap(), 1087);
+    fn small1() {
+        let interpreter = Interpreter::new(parse_instructions(SMALL).unwrap());
+        assert_eq!(final_acc(interpreter).unwrap(), 5);
     }
 
     #[test]
@@ -203,10 +1489,371 @@ mod test {
         let interpreter = Interpreter::new(parse_instructions(SMALL).unwrap());
         assert_eq!(final_fixed_acc(interpreter).unwrap(), 8);
     }
+
+    #[test]
+    fn visited() {
+        let mut visited = Visited::new();
+        assert!(visited.is_empty());
+        assert!(visited.insert(3) && visited.insert(130));
+        assert!(!visited.insert(130));
+        assert!(visited.contains(3) && !visited.contains(64) && !visited.contains(1000));
+        visited.remove(3);
+        assert_eq!(visited.len(), 1);
+    }
+
+    #[test]
+    fn corruption() {
+        let program = parse_instructions(SMALL).unwrap();
+        assert_eq!(find_corruption(&program), Some(7));
+        // No single swap fixes this, and the console's other instructions
+        // are not boot code.
+        assert_eq!(find_corruption(&assemble("jmp +0\njmp -1").unwrap()), None);
+        assert_eq!(find_corruption(&assemble("nop +0\nhlt").unwrap()), None);
+        // This terminates already, and still does with its `nop` swapped.
+        let program = parse_instructions("nop +2\nacc +1").unwrap();
+        assert_eq!(find_corruption(&program), Some(0));
+        assert_eq!(swap_fixes(&program), Some(vec![]));
+        // Swapping the `nop` here makes it jump back to itself forever.
+        let program = parse_instructions("nop +0\nacc +1").unwrap();
+        assert_eq!(find_corruption(&program), None);
+        let program = parse_instructions("nop +0\nnop +1\nacc +1").unwrap();
+        assert_eq!(find_corruption(&program), Some(1));
+        assert_eq!(final_fixed_acc(Interpreter::new(program)), Some(1));
+
+        // A long chain of jumps back to the start, where only the last
+        // `nop`'s jump escapes.
+        let mut program: Vec<Instruction> = (0..10_000).map(|_| Instruction::Acc(1)).collect();
+        program.push(Instruction::Nop(2));
+        program.push(Instruction::Jump(-10_001));
+        assert_eq!(find_corruption(&program), Some(10_000));
+        assert_eq!(final_fixed_acc(Interpreter::new(program)), Some(10_000));
+    }
+
+    const FACTORIAL: &str = r"
+        ; Writes the factorial of its input.
+                in r1
+                set acc 1
+        loop:   jz r1 done      # counting down
+                mul acc r1
+                sub r1 1
+                jmp loop
+        done:   out acc
+                hlt
+    ";
+
+    #[test]
+    fn assembler() {
+        let program = assemble(FACTORIAL).unwrap();
+        assert_eq!(program.len(), 8);
+        assert_eq!(program[2], Instruction::Branch(Condition::Zero, 1, 4));
+        assert_eq!(
+            program[3],
+            Instruction::Arith(ArithOp::Mul, ACC, Operand::Reg(1))
+        );
+        assert_eq!(program[5], Instruction::Jump(-3));
+        // Boot code assembles as it parses, and disassembly round-trips.
+        assert_eq!(assemble(SMALL).unwrap(), parse_instructions(SMALL).unwrap());
+        let listing: Vec<String> = program.iter().map(Instruction::to_string).collect();
+        assert_eq!(listing[2], "jz r1 +4");
+        assert_eq!(assemble(&listing.join("\n")).unwrap(), program);
+    }
+
+    #[test]
+    fn assembler_errors() {
+        let error = |source: &str| assemble(source).unwrap_err().to_string();
+        assert_eq!(
+            error("nop +0\nfoo r1"),
+            "day 8, line 2, column 1: expected an instruction, found `foo`"
+        );
+        assert_eq!(
+            error("add r9 1"),
+            "day 8, line 1, column 5: expected a register, found `r9`"
+        );
+        assert_eq!(
+            error("jnz acc nowhere"),
+            "day 8, line 1, column 9: expected an offset or label, found `nowhere`"
+        );
+        assert_eq!(
+            error("set acc # missing\n"),
+            "day 8, line 1, column 8: expected a register or number, found whitespace"
+        );
+        assert_eq!(
+            error("a: hlt\na: hlt"),
+            "day 8, line 2, column 1: expected a new label, found `a:`"
+        );
+        assert_eq!(
+            error("hlt now"),
+            "day 8, line 1, column 5: expected end of line, found `now`"
+        );
+    }
+
+    #[test]
+    fn registers() {
+        let program = assemble(FACTORIAL).unwrap();
+        let mut interpreter = Interpreter::unchecked(program.clone()).with_input(vec![5]);
+        assert_eq!(interpreter.drive(), Status::Complete);
+        assert_eq!(interpreter.output, vec![120]);
+
+        // Boot code's loop check would stop at the second pass through `loop`.
+        let mut interpreter = Interpreter::new(program.clone()).with_input(vec![5]);
+        assert_eq!(interpreter.drive(), Status::Loop);
+
+        let mut interpreter = Interpreter::unchecked(program);
+        assert_eq!(interpreter.drive(), Status::Waiting);
+        interpreter.input.push_back(0);
+        assert_eq!(interpreter.drive(), Status::Complete);
+        assert_eq!(interpreter.output, vec![1]);
+
+        let mut interpreter = Interpreter::new(assemble("div acc 0").unwrap());
+        assert_eq!(interpreter.drive(), Status::Error);
+        let mut interpreter = Interpreter::unchecked(assemble("l: jmp l").unwrap());
+        assert_eq!(interpreter.drive_for(100), None);
+    }
+
+    #[test]
+    fn debugger() {
+        let mut debugger = Debugger::new(Interpreter::new(parse_instructions(SMALL).unwrap()));
+        for _ in 0..3 {
+            assert_eq!(debugger.step(), None);
+        }
+        assert_eq!(
+            (debugger.interpreter.pos, debugger.interpreter.acc()),
+            (6, 1)
+        );
+        assert!(debugger.back());
+        assert_eq!(debugger.interpreter.pos, 2);
+
+        assert_eq!(debugger.run(), Stop::Halted(Status::Loop));
+        assert_eq!(
+            (debugger.interpreter.pos, debugger.interpreter.acc()),
+            (1, 5)
+        );
+        assert_eq!(
+            debugger.trace().collect::<Vec<_>>(),
+            vec![0, 1, 2, 6, 7, 3, 4]
+        );
+        assert_eq!(debugger.step(), Some(Status::Loop));
+        while debugger.back() {}
+        assert_eq!(debugger.interpreter.pos, 0);
+        assert!(debugger.interpreter.visited.is_empty());
+
+        debugger.breakpoints = vec![Breakpoint::Register(ACC, 2), Breakpoint::Pc(3)];
+        assert_eq!(
+            debugger.run(),
+            Stop::Breakpoint(Breakpoint::Register(ACC, 2))
+        );
+        assert_eq!(debugger.interpreter.pos, 7);
+        assert_eq!(debugger.run(), Stop::Breakpoint(Breakpoint::Pc(3)));
+        assert_eq!(debugger.run_to(4), Stop::Reached);
+    }
+
+    #[test]
+    fn reverse_io() {
+        let interpreter = Interpreter::unchecked(assemble(FACTORIAL).unwrap()).with_input(vec![3]);
+        let mut debugger = Debugger::new(interpreter);
+        assert_eq!(debugger.run(), Stop::Halted(Status::Complete));
+        assert_eq!(debugger.interpreter.output, vec![6]);
+        while debugger.back() {}
+        let it = &debugger.interpreter;
+        assert_eq!((it.pos, it.acc(), it.output.len()), (0, 0, 0));
+        assert_eq!(it.input, vec![3]);
+
+        debugger.limit = 10;
+        debugger.interpreter.program = assemble("l: jmp l").unwrap();
+        assert_eq!(debugger.run(), Stop::Limit);
+    }
+
+    #[test]
+    fn delete() {
+        let mut debugger = Debugger::new(Interpreter::new(parse_instructions(SMALL).unwrap()));
+        debugger.command("break pc 3").unwrap();
+        assert_eq!(
+            debugger.command("delete"),
+            Err("missing breakpoint number (usage: delete <i>)".to_owned())
+        );
+        assert_eq!(debugger.breakpoints, vec![Breakpoint::Pc(3)]);
+        assert_eq!(
+            debugger.command("delete 2"),
+            Err("no breakpoint 2".to_owned())
+        );
+        assert_eq!(debugger.command("delete 1"), Ok("deleted pc 3".to_owned()));
+        assert_eq!(debugger.breakpoints, vec![]);
+    }
+
     #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day08.input").unwrap();
-        let interpreter = Interpreter::new(parse_instructions(&raw).unwrap());
-        assert_eq!(final_fixed_acc(interpreter).unwrap(), 780);
+    fn repl() {
+        let mut debugger = Debugger::new(Interpreter::new(parse_instructions(SMALL).unwrap()));
+        let commands = "break acc 2\ncontinue\nregisters\ntrace 2\nback\nfoo\nquit\nstep\n";
+        let mut output = Vec::new();
+        debugger.repl(commands.as_bytes(), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "   0: nop +0\n(debug) breakpoint 1: acc 2\n\
+             (debug) breakpoint: acc 2\n   7: jmp -4\n\
+             (debug) pc=7 acc=2 r1=0 r2=0 r3=0 r4=0 r5=0 r6=0 r7=0\n\
+             (debug)    2: jmp +4\n   6: acc +1\n\
+             (debug)    6: acc +1\n\
+             (debug) error: unknown command: foo (try `help`)\n(debug) \n"
+        );
+    }
+
+    #[test]
+    fn analysis() {
+        let program = parse_instructions(SMALL).unwrap();
+        let analysis = Analysis::new(&program);
+        assert_eq!(
+            analysis.cfg.blocks,
+            vec![0..1, 1..3, 3..5, 5..6, 6..8, 8..9]
+        );
+        assert!(analysis.never_halts());
+        assert_eq!(
+            analysis.to_string(),
+            "blocks: 0, 1-2, 3-4, 5, 6-7, 8\n\
+             unreachable: 5, 8\n\
+             endless: 0, 1, 2, 3, 4, 6, 7\n\
+             fixes: 7\n"
+        );
+
+        let mut fixed = program;
+        fixed[7] = fixed[7].swapped().unwrap();
+        let analysis = Analysis::new(&fixed);
+        assert!(!analysis.never_halts());
+        assert_eq!((analysis.endless.len(), analysis.fixes.len()), (0, 0));
+
+        // Every reported fix terminates.
+        let looping = parse_instructions("nop +0\njmp +2\nnop -2\njmp -2\nacc +1").unwrap();
+        assert_eq!(Analysis::new(&looping).fixes, vec![3]);
+        for program in [parse_instructions(SMALL).unwrap(), looping] {
+            let analysis = Analysis::new(&program);
+            assert!(!analysis.fixes.is_empty());
+            for &pc in &analysis.fixes {
+                let mut fixed = program.clone();
+                fixed[pc] = fixed[pc].swapped().unwrap();
+                assert_eq!(Interpreter::new(fixed).drive(), Status::Complete);
+            }
+        }
+
+        let analysis = Analysis::new(&assemble(FACTORIAL).unwrap());
+        assert_eq!(analysis.cfg.blocks, vec![0..2, 2..3, 3..6, 6..8]);
+        assert_eq!(analysis.unreachable, Vec::<usize>::new());
+        assert_eq!(analysis.endless, Vec::<usize>::new());
+        let analysis = Analysis::new(&assemble("set r1 1\nl: jz r1 l\nhlt").unwrap());
+        assert_eq!(analysis.endless, Vec::<usize>::new());
+        let analysis = Analysis::new(&assemble("set r1 1\nl: jmp l\nhlt").unwrap());
+        assert_eq!(
+            (analysis.endless, analysis.unreachable),
+            (vec![0, 1], vec![2])
+        );
+        // Dividing by a register may fail, leaving the loop, but dividing by
+        // a constant other than 0 never does.
+        let program = assemble("l: div acc r1\njmp l\nhlt").unwrap();
+        let analysis = Analysis::new(&program);
+        assert_eq!(analysis.cfg.successors[0], vec![Node::Pc(1), Node::Outside]);
+        assert_eq!(analysis.endless, Vec::<usize>::new());
+        let analysis = Analysis::new(&assemble("l: mod acc 2\njmp l\nhlt").unwrap());
+        assert_eq!(analysis.endless, vec![0, 1]);
+        let analysis = Analysis::new(&assemble("div acc 0\nhlt").unwrap());
+        assert_eq!(analysis.cfg.successors[0], vec![Node::Outside]);
+        assert_eq!(analysis.unreachable, vec![1]);
+    }
+
+    #[test]
+    fn dot() {
+        let analysis = Analysis::new(&parse_instructions("nop +2 jmp -1 acc +1 jmp +1").unwrap());
+        assert_eq!(
+            analysis.dot(),
+            r#"digraph program {
+    node [shape=box, fontname=monospace];
+    b0 [label="0: nop +2  (fix)\l1: jmp -1  (fix)\l", color=red];
+    b1 [label="2: acc +1\l3: jmp +1\l", style=dashed];
+    end [shape=doublecircle];
+    start [shape=point];
+    start -> b0;
+    b0 -> b0;
+    b1 -> end;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn edits() {
+        let program = parse_instructions("nop +0 acc +1 jmp -2").unwrap();
+        let edits = [
+            Edit::Insert(0, Instruction::Halt),
+            Edit::SetAcc(1, 5),
+            Edit::Swap(2),
+            Edit::Insert(3, Instruction::Acc(2)),
+        ];
+        assert_eq!(
+            apply_edits(&program, &edits),
+            assemble("hlt\nnop +0\nacc +5\nnop -2\nacc +2").unwrap()
+        );
+        assert_eq!(
+            apply_edits(&program, &[Edit::Delete(0)]),
+            program[1..].to_vec()
+        );
+    }
+
+    #[test]
+    fn repair() {
+        let program = parse_instructions(SMALL).unwrap();
+        let fixes = |repair: Repair| -> Vec<String> {
+            repair.search(&program).iter().map(Fix::to_string).collect()
+        };
+        assert_eq!(fixes(Repair::default()), vec!["7: swap (acc 8)"]);
+
+        let deletions = Repair {
+            max_edits: 2,
+            deletions: true,
+            ..Repair::default()
+        };
+        let all = fixes(deletions.clone());
+        assert_eq!(all.len(), 8);
+        assert_eq!(
+            &all[..3],
+            [
+                "7: swap (acc 8)",
+                "7: delete (acc 8)",
+                "3: delete, 4: delete (acc 7)"
+            ]
+        );
+        let fewest = Repair {
+            objective: Objective::MinEdits,
+            ..deletions
+        };
+        assert_eq!(fixes(fewest), vec!["7: swap (acc 8)", "7: delete (acc 8)"]);
+
+        // Changing an `acc` fixes nothing alone, but pays off alongside a swap.
+        let max_acc = Repair {
+            max_edits: 2,
+            acc_values: vec![0, 10],
+            objective: Objective::MaxAcc,
+            ..Repair::default()
+        };
+        assert_eq!(
+            fixes(max_acc),
+            vec![
+                "1: acc +10, 7: swap (acc 17)",
+                "6: acc +10, 7: swap (acc 17)"
+            ]
+        );
+
+        let insertions = Repair {
+            swaps: false,
+            insertions: vec![Instruction::Jump(2)],
+            ..Repair::default()
+        };
+        assert_eq!(fixes(insertions), vec!["7: insert jmp +2 (acc 8)"]);
+
+        // A program that already terminates needs no edits.
+        let fixed = apply_edits(&program, &[Edit::Swap(7)]);
+        assert_eq!(
+            Repair::default().search(&fixed),
+            vec![Fix {
+                edits: vec![],
+                acc: 8
+            }]
+        );
     }
 }
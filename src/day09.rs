@@ -1,7 +1,28 @@
+//! Day 9: Encoding Error.
+
 use itertools::Itertools;
 use std::collections::VecDeque;
 
-fn first_flaw(xs: &[u64], buffer_size: usize) -> Option<u64> {
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 9;
+
+/// Parses one number per whitespace-separated token.
+pub fn parse(input: &str) -> Result<Vec<u64>, Error> {
+    input
+        .split_ascii_whitespace()
+        .map(|s| {
+            s.parse()
+                .map_err(|_| Error::parse(DAY, input, s, "a non-negative integer"))
+        })
+        .collect()
+}
+
+/// The first number that is not the sum of two of the `buffer_size` before it.
+pub fn first_flaw(xs: &[u64], buffer_size: usize) -> Option<u64> {
+    if xs.len() < buffer_size {
+        return None;
+    }
     let mut buffer = VecDeque::new();
     for &x in &xs[..buffer_size] {
         buffer.push_back(x);
@@ -16,8 +37,12 @@ fn first_flaw(xs: &[u64], buffer_size: usize) -> Option<u64> {
     None
 }
 
-fn contiguous_region(xs: &[u64], target: u64) -> Option<&[u64]> {
-    assert!(target > 0);
+/// A contiguous run of at least one number summing to `target`; `None` if
+/// there is none, or `target` is 0.
+pub fn contiguous_region(xs: &[u64], target: u64) -> Option<&[u64]> {
+    if target == 0 {
+        return None;
+    }
     let mut lo = 0;
     let mut acc = 0;
     for hi in 0..xs.len() {
@@ -33,9 +58,29 @@ fn contiguous_region(xs: &[u64], target: u64) -> Option<&[u64]> {
     None
 }
 
+/// [`Solution`] for day 9.
+pub struct Day09;
+impl Solution for Day09 {
+    type Input = Vec<u64>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<u64> {
+        first_flaw(input, 25)
+    }
+    fn part2(input: &Self::Input) -> Option<u64> {
+        let target = first_flaw(input, 25)?;
+        let region = contiguous_region(input, target)?;
+        Some(region.iter().min()? + region.iter().max()?)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{contiguous_region, first_flaw};
+    use super::{contiguous_region, first_flaw, parse};
 
     const SMALL: &str = r"
         35
@@ -62,47 +107,18 @@ mod test {
 
     #[test]
     fn small1() {
-        let input: Vec<u64> = SMALL
-            .split_ascii_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
+        let input = parse(SMALL).unwrap();
         assert_eq!(first_flaw(&input, 5).unwrap(), 127);
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day09.input").unwrap();
-        let input: Vec<u64> = raw
-            .split_ascii_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-        assert_eq!(first_flaw(&input, 25).unwrap(), 375054920);
-    }
-
     #[test]
     fn small2() {
-        let input: Vec<u64> = SMALL
-            .split_ascii_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
+        let input = parse(SMALL).unwrap();
         let target = first_flaw(&input, 5).unwrap();
         let region = contiguous_region(&input, target).unwrap();
         let min = *region.iter().min().unwrap();
         let max = *region.iter().max().unwrap();
         assert_eq!(min + max, 62);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day09.input").unwrap();
-        let input: Vec<u64> = raw
-            .split_ascii_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-        let target = first_flaw(&input, 25).unwrap();
-        let region = contiguous_region(&input, target).unwrap();
-        let min = *region.iter().min().unwrap();
-        let max = *region.iter().max().unwrap();
-        assert_eq!(min + max, 54142584);
+        assert_eq!(contiguous_region(&[1, 0, 2], 0), None);
     }
 }
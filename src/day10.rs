@@ -1,6 +1,33 @@
+//! Day 10: Adapter Array.
+
 use std::collections::HashMap;
 
-fn tally_diffs(xs: &[u32]) -> HashMap<u32, usize> {
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 10;
+
+/// Parses adapter joltages, adding the outlet (0) and the device (max + 3).
+pub fn parse(input: &str) -> Result<Vec<u32>, Error> {
+    let mut real: Vec<u32> = input
+        .split_ascii_whitespace()
+        .map(|s| {
+            s.parse()
+                .map_err(|_| Error::parse(DAY, input, s, "a joltage"))
+        })
+        .collect::<Result<_, _>>()?;
+    let max = *real
+        .iter()
+        .max()
+        .ok_or_else(|| Error::parse(DAY, input, &input[input.len()..], "a joltage"))?;
+    // The socket is effectively 0.
+    real.push(0);
+    // The device is effectively max(adapters) + 3
+    real.push(max + 3);
+    Ok(real)
+}
+
+/// Counts each difference between consecutive joltages in the sorted chain.
+pub fn tally_diffs(xs: &[u32]) -> HashMap<u32, usize> {
     let mut sorted = xs.to_vec();
     sorted.sort();
 
@@ -11,7 +38,8 @@ fn tally_diffs(xs: &[u32]) -> HashMap<u32, usize> {
     counts
 }
 
-fn count_arrangements(xs: &[u32]) -> u64 {
+/// Number of distinct adapter chains from the outlet to the device.
+pub fn count_arrangements(xs: &[u32]) -> u64 {
     let mut sorted = xs.to_vec();
     sorted.sort();
 
@@ -30,55 +58,62 @@ fn count_arrangements(xs: &[u32]) -> u64 {
     }
     *counts.last().unwrap()
 }
+/// [`Solution`] for day 10.
+pub struct Day10;
+impl Solution for Day10 {
+    type Input = Vec<u32>;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        let diffs = tally_diffs(input);
+        Some(diffs.get(&1)? * diffs.get(&3)?)
+    }
+    fn part2(input: &Self::Input) -> Option<u64> {
+        Some(count_arrangements(input))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{count_arrangements, tally_diffs};
+    use super::{count_arrangements, parse, tally_diffs};
 
     const TINY: &str = r"16 10 15 5 1 11 7 19 6 12 4";
     const SMALL: &str = r"
         28 33 18 42 31 14 46 20 48 47 24 23 49 45 19
         38 39 11 1 32 25 35 8 17 7 9 4 2 34 10 3
     ";
-    fn parse(input: &str) -> Vec<u32> {
-        let mut real: Vec<u32> = input
-            .split_ascii_whitespace()
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect();
-        // The socket is effectively 0.
-        real.push(0);
-        // The device is effectively max(adapters) + 3
-        real.push(real.iter().max().unwrap() + 3);
-        real
+    #[test]
+    fn parser() {
+        assert_eq!(parse("3 1").unwrap(), vec![3, 1, 0, 6]);
+        assert_eq!(
+            parse("").unwrap_err().to_string(),
+            "day 10, line 1, column 1: expected a joltage, found end of input"
+        );
+        assert_eq!(
+            parse("3\n  x").unwrap_err().to_string(),
+            "day 10, line 2, column 3: expected a joltage, found `x`"
+        );
     }
 
     #[test]
     fn small1() {
         assert_eq!(
-            tally_diffs(&parse(TINY)),
+            tally_diffs(&parse(TINY).unwrap()),
             vec![(1, 7), (3, 5)].into_iter().collect()
         );
         assert_eq!(
-            tally_diffs(&parse(SMALL)),
+            tally_diffs(&parse(SMALL).unwrap()),
             vec![(1, 22), (3, 10)].into_iter().collect()
         );
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day10.input").unwrap();
-        let diffs = tally_diffs(&parse(&raw));
-        assert_eq!(diffs[&1] * diffs[&3], 2100);
-    }
-
     #[test]
     fn small2() {
-        assert_eq!(count_arrangements(&parse(TINY)), 8);
-        assert_eq!(count_arrangements(&parse(SMALL)), 19208);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day10.input").unwrap();
-        assert_eq!(count_arrangements(&parse(&raw)), 16198260678656);
+        assert_eq!(count_arrangements(&parse(TINY).unwrap()), 8);
+        assert_eq!(count_arrangements(&parse(SMALL).unwrap()), 19208);
     }
 }
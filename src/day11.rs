@@ -1,18 +1,22 @@
+//! Day 11: Seating System.
+
 use std::fmt;
 
-use crate::grid::{Grid, Pos};
+use crate::automaton::{Automaton, Dense, LifeRule, Outcome, Rule};
+use crate::grid::{Grid, Pos, DIRECTIONS8};
+use crate::recorder::Recorder;
+use crate::{error::Error, solution::Solution};
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
-enum Cell {
+const DAY: u8 = 11;
+
+/// One position in the waiting area.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, Default)]
+pub enum Cell {
     Occupied,
     Vacant,
+    #[default]
     Floor,
 }
-impl Default for Cell {
-    fn default() -> Self {
-        Cell::Floor
-    }
-}
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
@@ -23,99 +27,133 @@ impl fmt::Display for Cell {
     }
 }
 
-fn step(grid: &Grid<Cell>) -> Grid<Cell> {
-    Grid::from_fn(grid.width, grid.height, |pos| {
-        let occupied = neighbors(pos)
-            .into_iter()
-            .filter(|&p| grid.get(p) == Some(&Cell::Occupied))
-            .count();
-        let cur = *grid.get(pos).unwrap();
-        if cur == Cell::Vacant && occupied == 0 {
-            Cell::Occupied
-        } else if cur == Cell::Occupied && occupied >= 4 {
-            Cell::Vacant
-        } else {
-            cur
-        }
+/// Parses a seat layout of `L`, `#` and `.`.
+pub fn parse(input: &str) -> Result<Grid<Cell>, Error> {
+    Grid::parse(DAY, input, "`L`, `#` or `.`", |c| match c {
+        'L' => Some(Cell::Vacant),
+        '#' => Some(Cell::Occupied),
+        '.' => Some(Cell::default()),
+        _ => None,
     })
 }
 
-fn step2(grid: &Grid<Cell>) -> Grid<Cell> {
-    Grid::from_fn(grid.width, grid.height, |pos| {
-        let occupied = rays(pos)
-            .into_iter()
-            .map(|r| {
-                let visible = r.map(|p| grid.get(p)).find(|&c| c != Some(&Cell::Floor));
-                if visible == Some(Some(&Cell::Occupied)) {
-                    1
-                } else {
-                    0
-                }
-            })
-            .sum::<usize>();
-        let cur = *grid.get(pos).unwrap();
-        if cur == Cell::Vacant && occupied == 0 {
-            Cell::Occupied
-        } else if cur == Cell::Occupied && occupied >= 5 {
-            Cell::Vacant
-        } else {
-            cur
-        }
-    })
+/// Number of occupied seats.
+pub fn count_occupied(grid: &Grid<Cell>) -> usize {
+    grid.items.iter().filter(|&&c| c == Cell::Occupied).count()
 }
 
-fn neighbors((i, j): Pos) -> Vec<Pos> {
-    vec![
-        (i - 1, j - 1),
-        (i - 1, j),
-        (i - 1, j + 1),
-        (i, j - 1),
-        (i, j + 1),
-        (i + 1, j - 1),
-        (i + 1, j),
-        (i + 1, j + 1),
-    ]
+/// Which seats count as a seat's neighbors.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Neighborhood {
+    /// The 8 surrounding positions.
+    Adjacent,
+    /// The first seat seen in each of the 8 directions.
+    Visible,
 }
 
-fn rays((i, j): Pos) -> Vec<impl Iterator<Item = Pos>> {
-    let ray = |di, dj| (1..).map(move |k| (i + k * di, j + k * dj));
-    vec![
-        ray(-1, -1),
-        ray(-1, 0),
-        ray(-1, 1),
-        ray(0, -1),
-        ray(0, 1),
-        ray(1, -1),
-        ray(1, 0),
-        ray(1, 1),
-    ]
+/// The rule for adjacent seats: a vacant seat fills when no neighbor is
+/// occupied, and an occupied one empties once 4 are.
+pub const RULE: &str = "B0/S0123";
+/// The rule for visible seats, where it takes 5 occupied seats to empty one.
+pub const RULE2: &str = "B0/S01234";
+
+fn adjacent(grid: &Grid<Cell>, pos: Pos) -> Vec<Pos> {
+    grid.neighbors8(pos).collect()
+}
+fn visible(grid: &Grid<Cell>, pos: Pos) -> Vec<Pos> {
+    DIRECTIONS8
+        .iter()
+        .filter_map(|&dir| grid.ray(pos, dir).find(|&(_, &c)| c != Cell::Floor))
+        .map(|(p, _)| p)
+        .collect()
+}
+
+type Neighbors = fn(&Grid<Cell>, Pos) -> Vec<Pos>;
+
+fn automaton(
+    neighborhood: Neighborhood,
+    rule: LifeRule,
+) -> Automaton<Dense<Cell, Neighbors>, impl Fn(&Cell, usize) -> Cell> {
+    let neighbors: Neighbors = match neighborhood {
+        Neighborhood::Adjacent => adjacent,
+        Neighborhood::Visible => visible,
+    };
+    let seating = move |&cur: &Cell, occupied: usize| match cur {
+        Cell::Floor => Cell::Floor,
+        _ if rule.apply(&(cur == Cell::Occupied), occupied) => Cell::Occupied,
+        _ => Cell::Vacant,
+    };
+    let is_occupied = |cell: &Cell| *cell == Cell::Occupied;
+    Automaton::new(Dense::new(neighbors, is_occupied), seating)
 }
 
-fn stabilize(mut grid: Grid<Cell>) -> Grid<Cell> {
-    loop {
-        let next = step(&grid);
-        if grid == next {
-            return grid;
-        }
-        grid = next;
+/// Applies `rule` until the seating repeats itself, treating occupied seats
+/// as live and vacant ones as dead. Floor never changes.
+pub fn simulate(
+    grid: Grid<Cell>,
+    neighborhood: Neighborhood,
+    rule: LifeRule,
+) -> Outcome<Grid<Cell>> {
+    // There are finitely many seatings, so one always repeats.
+    automaton(neighborhood, rule).settle(grid, usize::MAX)
+}
+
+/// Records every seating [`simulate`] goes through, up to the first repeat.
+pub fn record(
+    grid: Grid<Cell>,
+    neighborhood: Neighborhood,
+    rule: LifeRule,
+    recorder: &mut Recorder,
+) {
+    let automaton = automaton(neighborhood, rule);
+    let frames = match automaton.settle(grid.clone(), usize::MAX) {
+        Outcome::Stable { generation, .. } => generation + 1,
+        Outcome::Cycle {
+            transient, period, ..
+        } => transient + period,
+        Outcome::Unsettled { generations, .. } => generations + 1,
+    };
+    for generation in automaton.generations(grid).take(frames) {
+        recorder.record(&generation);
     }
 }
 
-fn stabilize2(mut grid: Grid<Cell>) -> Grid<Cell> {
-    loop {
-        let next = step2(&grid);
-        if grid == next {
-            return grid;
-        }
-        grid = next;
+/// Applies the adjacent-seat rules until nothing changes; `None` if the
+/// seating cycles instead.
+pub fn stabilize(grid: Grid<Cell>) -> Option<Grid<Cell>> {
+    simulate(grid, Neighborhood::Adjacent, RULE.parse().unwrap()).stable()
+}
+
+/// Applies the line-of-sight rules until nothing changes; `None` if the
+/// seating cycles instead.
+pub fn stabilize2(grid: Grid<Cell>) -> Option<Grid<Cell>> {
+    simulate(grid, Neighborhood::Visible, RULE2.parse().unwrap()).stable()
+}
+
+/// [`Solution`] for day 11.
+pub struct Day11;
+impl Solution for Day11 {
+    type Input = Grid<Cell>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(count_occupied(&stabilize(input.clone())?))
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(count_occupied(&stabilize2(input.clone())?))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::grid::Grid;
-
-    use super::{stabilize, stabilize2, Cell};
+    use super::{
+        count_occupied, parse, record, simulate, stabilize, stabilize2, Neighborhood, RULE,
+    };
+    use crate::{automaton::Outcome, recorder::Recorder};
 
     const SMALL: &str = r"
         L.LL.LL.LL
@@ -130,62 +168,85 @@ mod test {
         L.LLLLL.LL
     ";
 
-    fn parse(input: &str) -> Grid<Cell> {
-        let lines: Vec<&str> = input
-            .lines()
-            .map(|l| l.trim())
-            .filter(|line| !line.is_empty())
-            .collect();
-        Grid::new(
-            lines[0].len(),
-            lines.len(),
-            lines
-                .into_iter()
-                .flat_map(|l| l.chars())
-                .map(|c| match c {
-                    'L' => Cell::Vacant,
-                    '#' => Cell::Occupied,
-                    '.' => Cell::default(),
-                    _ => panic!("illegal char"),
-                })
-                .collect(),
-        )
+    #[test]
+    fn parser() {
+        let grid = parse("L.\n#L").unwrap();
+        assert_eq!((grid.width, grid.height), (2, 2));
+        assert_eq!(count_occupied(&grid), 1);
+        assert_eq!(
+            parse("L.\nL").unwrap_err().to_string(),
+            "day 11, line 2, column 2: expected a row of width 2, found end of input"
+        );
+        assert_eq!(
+            parse("L.\nL?").unwrap_err().to_string(),
+            "day 11, line 2, column 2: expected `L`, `#` or `.`, found `?`"
+        );
+        assert!(parse("").is_err());
     }
+
     #[test]
     fn small1() {
-        let grid = parse(SMALL);
-        let stable = stabilize(grid);
-        let occupied = stable
-            .items
-            .into_iter()
-            .filter(|&c| c == Cell::Occupied)
-            .count();
+        let grid = parse(SMALL).unwrap();
+        let stable = stabilize(grid).unwrap();
+        let occupied = count_occupied(&stable);
         assert_eq!(occupied, 37);
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day11.input").unwrap();
-        let grid = parse(&raw);
-        let stable = stabilize(grid);
-        let occupied = stable
-            .items
-            .into_iter()
-            .filter(|&c| c == Cell::Occupied)
-            .count();
-        assert_eq!(occupied, 2238);
+    fn small2() {
+        let grid = parse(SMALL).unwrap();
+        let stable = stabilize2(grid).unwrap();
+        assert_eq!(count_occupied(&stable), 26);
+    }
+
+    #[test]
+    fn rules() {
+        let grid = parse(SMALL).unwrap();
+        // Seats fill up and never empty.
+        let full = simulate(
+            grid.clone(),
+            Neighborhood::Visible,
+            "B0/S012345678".parse().unwrap(),
+        );
+        assert_eq!(count_occupied(&full.stable().unwrap()), 71);
+        let none = simulate(grid, Neighborhood::Adjacent, "B/S".parse().unwrap());
+        assert_eq!(count_occupied(&none.stable().unwrap()), 0);
+    }
+
+    #[test]
+    fn cycles() {
+        // Lone seats fill, then empty again because nobody stays.
+        let grid = parse("L.L").unwrap();
+        assert_eq!(
+            simulate(
+                grid.clone(),
+                Neighborhood::Adjacent,
+                "B0/S".parse().unwrap()
+            ),
+            Outcome::Cycle {
+                transient: 0,
+                period: 2,
+                world: grid.clone()
+            }
+        );
+        assert_eq!(
+            stabilize(parse("L.L").unwrap()).map(|g| count_occupied(&g)),
+            Some(2)
+        );
     }
 
     #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day11.input").unwrap();
-        let grid = parse(&raw);
-        let stable = stabilize2(grid);
-        let occupied = stable
-            .items
-            .into_iter()
-            .filter(|&c| c == Cell::Occupied)
-            .count();
-        assert_eq!(occupied, 2013);
+    fn recording() {
+        let mut recorder = Recorder::new();
+        record(
+            parse(SMALL).unwrap(),
+            Neighborhood::Adjacent,
+            RULE.parse().unwrap(),
+            &mut recorder,
+        );
+        // The example settles after 5 rounds, then repeats once.
+        assert_eq!(recorder.frames.len(), 6);
+        let text = recorder.frames[1].to_string();
+        assert!(text.starts_with("Generation 1:\n\n#.##.##.##\n#######.##\n"));
     }
 }
@@ -1,5 +1,12 @@
-#[derive(Eq, PartialEq)]
-enum Move {
+//! Day 12: Rain Risk.
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 12;
+
+/// A navigation action; each is paired with its value in the instructions.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Move {
     North,
     South,
     East,
@@ -9,10 +16,38 @@ enum Move {
     Forward,
 }
 
+/// Parses instructions such as `F10 N3 R90`.
+pub fn parse(input: &str) -> Result<Vec<(Move, i32)>, Error> {
+    input
+        .split_ascii_whitespace()
+        .map(|s| {
+            let m = match s.get(0..1) {
+                Some("N") => Move::North,
+                Some("S") => Move::South,
+                Some("E") => Move::East,
+                Some("W") => Move::West,
+                Some("F") => Move::Forward,
+                Some("L") => Move::Left,
+                Some("R") => Move::Right,
+                _ => return Err(Error::parse(DAY, input, s, "one of `NSEWFLR`")),
+            };
+            let arg = &s[1..];
+            match (m, arg.parse()) {
+                (Move::Left | Move::Right, Ok(n)) if [90, 180, 270].contains(&n) => Ok((m, n)),
+                (Move::Left | Move::Right, _) => {
+                    Err(Error::parse(DAY, input, arg, "`90`, `180` or `270`"))
+                }
+                (_, Ok(n)) => Ok((m, n)),
+                (_, Err(_)) => Err(Error::parse(DAY, input, arg, "a distance")),
+            }
+        })
+        .collect()
+}
+
 struct Ship {
     x: i32,
     y: i32,
-    theta: i32, // in degrees
+    theta: i32, // in degrees, a multiple of 90 in 0..360
 }
 impl Ship {
     fn new() -> Ship {
@@ -22,23 +57,28 @@ impl Ship {
             theta: 0,
         }
     }
-    fn step(&mut self, m: Move, n: i32) {
+    /// `None` for a turn that is not a multiple of 90 degrees.
+    fn step(&mut self, m: Move, n: i32) -> Option<()> {
         match m {
             Move::North => self.y += n,
             Move::South => self.y -= n,
             Move::East => self.x += n,
             Move::West => self.x -= n,
-            Move::Left => self.theta += n,
-            Move::Right => self.theta -= n,
+            Move::Left | Move::Right => {
+                if n % 90 != 0 {
+                    return None;
+                }
+                let n = if m == Move::Left { n } else { -n };
+                self.theta = (self.theta + n).rem_euclid(360);
+            }
             Move::Forward => match self.theta {
                 0 => self.x += n,
                 90 => self.y += n,
                 180 => self.x -= n,
-                270 => self.y -= n,
-                _ => panic!("invalid angle: {}", self.theta),
+                _ => self.y -= n,
             },
         }
-        self.theta = ((self.theta % 360) + 360) % 360;
+        Some(())
     }
 }
 
@@ -57,19 +97,21 @@ impl Ship2 {
             dy: 1,
         }
     }
-    fn step(&mut self, m: Move, n: i32) {
+    /// `None` for a turn that is not a multiple of 90 degrees.
+    fn step(&mut self, m: Move, n: i32) -> Option<()> {
         match m {
             Move::North => self.dy += n,
             Move::South => self.dy -= n,
             Move::East => self.dx += n,
             Move::West => self.dx -= n,
             Move::Left | Move::Right => {
-                let theta = if m == Move::Left { n } else { 360 - n };
-                let (dx, dy) = match theta {
+                let theta = if m == Move::Left { n } else { -n };
+                let (dx, dy) = match theta.rem_euclid(360) {
+                    0 => (self.dx, self.dy),
                     90 => (-self.dy, self.dx),
                     180 => (-self.dx, -self.dy),
                     270 => (self.dy, -self.dx),
-                    _ => panic!("invalid angle: {}", theta),
+                    _ => return None,
                 };
                 self.dx = dx;
                 self.dy = dy;
@@ -79,73 +121,78 @@ impl Ship2 {
                 self.y += n * self.dy;
             }
         }
+        Some(())
+    }
+}
+
+/// Manhattan distance travelled when the actions move the ship itself;
+/// `None` if a turn is not a multiple of 90 degrees.
+pub fn solve1(moves: &[(Move, i32)]) -> Option<i32> {
+    let mut ship = Ship::new();
+    for &(m, n) in moves {
+        ship.step(m, n)?;
+    }
+    Some(ship.x.abs() + ship.y.abs())
+}
+
+/// Manhattan distance travelled when the actions move a waypoint; `None` if
+/// a turn is not a multiple of 90 degrees.
+pub fn solve2(moves: &[(Move, i32)]) -> Option<i32> {
+    let mut ship = Ship2::new();
+    for &(m, n) in moves {
+        ship.step(m, n)?;
+    }
+    Some(ship.x.abs() + ship.y.abs())
+}
+
+/// [`Solution`] for day 12.
+pub struct Day12;
+impl Solution for Day12 {
+    type Input = Vec<(Move, i32)>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<i32> {
+        solve1(input)
+    }
+    fn part2(input: &Self::Input) -> Option<i32> {
+        solve2(input)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Move, Ship, Ship2};
+    use super::{parse, solve1, solve2, Move};
 
     const SMALL: &str = r" F10 N3 F7 R90 F11 ";
-    fn parse(input: &str) -> Vec<(Move, i32)> {
-        input
-            .trim()
-            .split_ascii_whitespace()
-            .map(|s| {
-                let m = match &s[0..1] {
-                    "N" => Move::North,
-                    "S" => Move::South,
-                    "E" => Move::East,
-                    "W" => Move::West,
-                    "F" => Move::Forward,
-                    "L" => Move::Left,
-                    "R" => Move::Right,
-                    _ => panic!("invalid move: {}", s),
-                };
-                let n = s[1..].parse().unwrap();
-                (m, n)
-            })
-            .collect()
-    }
     #[test]
-    fn small1() {
-        let input = parse(SMALL);
-        let mut ship = Ship::new();
-        for (m, n) in input {
-            ship.step(m, n);
-        }
-        assert_eq!(ship.x.abs() + ship.y.abs(), 25);
+    fn parser() {
+        assert_eq!(parse(SMALL).unwrap().len(), 5);
+        assert_eq!(
+            parse("F10 X3").unwrap_err().to_string(),
+            "day 12, line 1, column 5: expected one of `NSEWFLR`, found `X3`"
+        );
+        assert_eq!(
+            parse("F10\nR45").unwrap_err().to_string(),
+            "day 12, line 2, column 2: expected `90`, `180` or `270`, found `45`"
+        );
+        assert!(parse("F1O").is_err());
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day12.input").unwrap();
-        let input = parse(&raw);
-        let mut ship = Ship::new();
-        for (m, n) in input {
-            ship.step(m, n);
-        }
-        assert_eq!(ship.x.abs() + ship.y.abs(), 439);
+    fn small1() {
+        assert_eq!(solve1(&parse(SMALL).unwrap()), Some(25));
+        assert_eq!(solve1(&[(Move::Right, 450), (Move::Forward, 2)]), Some(2));
+        assert_eq!(solve1(&[(Move::Right, 45), (Move::Forward, 2)]), None);
     }
 
     #[test]
     fn small2() {
-        let input = parse(SMALL);
-        let mut ship = Ship2::new();
-        for (m, n) in input {
-            ship.step(m, n);
-        }
-        assert_eq!(ship.x.abs() + ship.y.abs(), 286);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day12.input").unwrap();
-        let input = parse(&raw);
-        let mut ship = Ship2::new();
-        for (m, n) in input {
-            ship.step(m, n);
-        }
-        assert_eq!(ship.x.abs() + ship.y.abs(), 12385);
+        assert_eq!(solve2(&parse(SMALL).unwrap()), Some(286));
+        assert_eq!(solve2(&[(Move::Left, 0), (Move::Forward, 1)]), Some(11));
+        assert_eq!(solve2(&[(Move::Left, 45), (Move::Forward, 1)]), None);
     }
 }
@@ -1,32 +1,74 @@
+//! Day 13: Shuttle Search.
+
 use itertools::Itertools;
-struct Schedule {
-    lo: i64,
-    ts: Vec<Option<i64>>,
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 13;
+
+/// The earliest departure time and the bus IDs in service (`None` for `x`).
+pub struct Schedule {
+    pub lo: i64,
+    pub ts: Vec<Option<i64>>,
+}
+
+/// Parses the two-line schedule notes.
+pub fn parse(input: &str) -> Result<Schedule, Error> {
+    let end = &input[input.len()..];
+    let mut lines = input.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+    let lo = lines
+        .next()
+        .ok_or_else(|| Error::parse(DAY, input, end, "the earliest timestamp"))?;
+    let lo = lo
+        .parse()
+        .map_err(|_| Error::parse(DAY, input, lo, "the earliest timestamp"))?;
+    let ts: Vec<Option<i64>> = lines
+        .next()
+        .ok_or_else(|| Error::parse(DAY, input, end, "a list of bus IDs"))?
+        .split(',')
+        .map(|t| match t {
+            "x" => Ok(None),
+            _ => t
+                .parse()
+                .ok()
+                .filter(|&id| id > 0)
+                .map(Some)
+                .ok_or_else(|| Error::parse(DAY, input, t, "a bus ID or `x`")),
+        })
+        .collect::<Result<_, _>>()?;
+    if ts.iter().all(Option::is_none) {
+        return Err(Error::parse(DAY, input, end, "at least one bus ID"));
+    }
+    Ok(Schedule { lo, ts })
 }
 
-fn solve1(input: Schedule) -> i64 {
+/// Bus ID times minutes waited for the earliest bus; `None` if there are no
+/// buses.
+pub fn solve1(input: &Schedule) -> Option<i64> {
     let (id, t) = input
         .ts
         .iter()
         .filter_map(|id| id.as_ref())
         .map(|&id| (id, id - (input.lo % id)))
-        .min_by_key(|&(_id, t)| t)
-        .unwrap();
-    id * t
+        .min_by_key(|&(_id, t)| t)?;
+    Some(id * t)
 }
 
-fn solve2(ts: Vec<Option<i64>>) -> i64 {
-    for (&t1, &t2) in ts.iter().filter_map(|t| t.as_ref()).tuple_combinations() {
-        assert_eq!(gcd(t1 as i64, t2 as i64), 1);
+/// Earliest timestamp at which each bus departs at its offset in the list;
+/// `None` unless the bus IDs are pairwise coprime.
+pub fn solve2(ts: &[Option<i64>]) -> Option<i64> {
+    let ids = ts.iter().filter_map(|t| t.as_ref());
+    if ids.tuple_combinations().any(|(&t1, &t2)| gcd(t1, t2) != 1) {
+        return None;
     }
     // We need t such that:
     //   t === 0 (mod ts[0])
     //   t === -1 (mod ts[1])
     //   t === -2 (mod ts[2])
     let mut eqns: Vec<(i64, i64)> = ts
-        .into_iter()
+        .iter()
         .enumerate()
-        .filter_map(|(idx, t)| Some((idx as i64, t? as i64)))
+        .filter_map(|(idx, &t)| Some((idx as i64, t?)))
         .collect();
     eqns.sort_by_key(|(_idx, t)| -t);
 
@@ -35,10 +77,10 @@ fn solve2(ts: Vec<Option<i64>>) -> i64 {
     for (idx, t) in eqns {
         // looking for x + k * dx === -i (mod t)
         let k = (0..).find(|k| (x + k * dx + idx) % t == 0).unwrap();
-        x = x + k * dx;
+        x += k * dx;
         dx *= t;
     }
-    x
+    Some(x)
 }
 
 fn gcd(m: i64, n: i64) -> i64 {
@@ -48,46 +90,64 @@ fn gcd(m: i64, n: i64) -> i64 {
         gcd(n, m % n)
     }
 }
+/// [`Solution`] for day 13.
+pub struct Day13;
+impl Solution for Day13 {
+    type Input = Schedule;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<i64> {
+        solve1(input)
+    }
+    fn part2(input: &Self::Input) -> Option<i64> {
+        solve2(&input.ts)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{gcd, solve1, solve2, Schedule};
+    use super::{gcd, parse, solve1, solve2, Schedule};
 
     const SMALL: &str = r"
         939
         7,13,x,x,59,x,31,19
     ";
-    fn parse(input: &str) -> Schedule {
-        let mut lines = input.trim().lines().map(|l| l.trim());
-        let lo = lines.next().unwrap().parse().unwrap();
-        let ts = lines
-            .next()
-            .unwrap()
-            .split(',')
-            .map(|t| t.parse().ok())
-            .collect();
-        Schedule { lo, ts }
-    }
     #[test]
-    fn small1() {
-        let input = parse(SMALL);
-        assert_eq!(solve1(input), 295);
+    fn parser() {
+        let schedule = parse(SMALL).unwrap();
+        assert_eq!(schedule.lo, 939);
+        assert_eq!(schedule.ts[..3], [Some(7), Some(13), None]);
+        assert!(parse("939").is_err());
+        assert!(parse("939\n7,y,13").is_err());
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day13.input").unwrap();
-        let input = parse(&raw);
-        assert_eq!(solve1(input), 153);
+    fn small1() {
+        let input = parse(SMALL).unwrap();
+        assert_eq!(solve1(&input), Some(295));
+        let input = Schedule {
+            lo: 939,
+            ts: vec![None],
+        };
+        assert_eq!(solve1(&input), None);
     }
 
     #[test]
     fn small2() {
-        assert_eq!(solve2(parse(SMALL).ts), 1068781);
-        assert_eq!(solve2(parse("0\n17,x,13,19").ts), 3417);
-        assert_eq!(solve2(parse("0\n67,7,59,61").ts), 754018);
-        assert_eq!(solve2(parse("0\n67,x,7,59,61").ts), 779210);
-        assert_eq!(solve2(parse("0\n67,7,x,59,61").ts), 1261476);
-        assert_eq!(solve2(parse("0\n1789,37,47,1889").ts), 1202161486);
+        assert_eq!(solve2(&parse(SMALL).unwrap().ts), Some(1068781));
+        assert_eq!(solve2(&parse("0\n17,x,13,19").unwrap().ts), Some(3417));
+        assert_eq!(solve2(&parse("0\n67,7,59,61").unwrap().ts), Some(754018));
+        assert_eq!(solve2(&parse("0\n67,x,7,59,61").unwrap().ts), Some(779210));
+        assert_eq!(solve2(&parse("0\n67,7,x,59,61").unwrap().ts), Some(1261476));
+        assert_eq!(
+            solve2(&parse("0\n1789,37,47,1889").unwrap().ts),
+            Some(1202161486)
+        );
+        assert_eq!(solve2(&parse("939\n4,6").unwrap().ts), None);
     }
 
     #[test]
@@ -96,11 +156,4 @@ mod test {
         assert_eq!(gcd(4, 10), 2);
         assert_eq!(gcd(13, 19), 1);
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day13.input").unwrap();
-        let input = parse(&raw);
-        assert_eq!(solve2(input.ts), 471793476184394);
-    }
 }
@@ -1,50 +1,105 @@
+//! Day 14: Docking Data.
+
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::collections::HashMap;
 
-enum Op {
-    SetMask(Vec<u8>),
+use crate::{error::Error, solution::Solution};
+
+/// One bit of a mask, most significant first.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum MaskBit {
+    Zero,
+    One,
+    Floating,
+}
+
+/// A line of the initialization program.
+pub enum Op {
+    SetMask(Vec<MaskBit>),
     Write { addr: u64, value: u64 },
 }
 
+const DAY: u8 = 14;
+
+lazy_static! {
+    static ref MASK: Regex = Regex::new(r"^mask = ([01X]{36})$").unwrap();
+    static ref WRITE: Regex = Regex::new(r"^mem\[([[:digit:]]+)\] = ([[:digit:]]+)$").unwrap();
+}
+/// Parses `mask = ...` and `mem[addr] = value` lines.
+pub fn parse(input: &str) -> Result<Vec<Op>, Error> {
+    input
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|s| {
+            if let Some(m) = MASK.captures(s) {
+                let mask = m.get(1).unwrap().as_str();
+                let bits = mask.bytes().map(|b| match b {
+                    b'0' => MaskBit::Zero,
+                    b'1' => MaskBit::One,
+                    _ => MaskBit::Floating,
+                });
+                Ok(Op::SetMask(bits.collect()))
+            } else if let Some(m) = WRITE.captures(s) {
+                let number = |idx| {
+                    let raw = m.get(idx).unwrap().as_str();
+                    raw.parse()
+                        .map_err(|_| Error::parse(DAY, input, raw, "a 64-bit number"))
+                };
+                Ok(Op::Write {
+                    addr: number(1)?,
+                    value: number(2)?,
+                })
+            } else {
+                let expected = "`mask = <36 of 0, 1 or X>` or `mem[<addr>] = <value>`";
+                Err(Error::parse(DAY, input, s, expected))
+            }
+        })
+        .collect()
+}
+
 const BITS: usize = 36;
-fn solve1(ops: Vec<Op>) -> HashMap<u64, u64> {
+/// Memory after running with the mask applied to values.
+pub fn solve1(ops: &[Op]) -> HashMap<u64, u64> {
     let mut registers = HashMap::new();
-    let mut mask = vec![0; BITS];
+    let mut mask: &[MaskBit] = &[MaskBit::Floating; BITS];
     for op in ops {
         match op {
             Op::SetMask(m) => {
                 mask = m;
             }
             Op::Write { addr, value } => {
-                registers.insert(addr, mask_value(&mask, value));
+                registers.insert(*addr, mask_value(mask, *value));
             }
         };
     }
     registers
 }
 
-fn mask_value(mask: &[u8], value: u64) -> u64 {
+fn mask_value(mask: &[MaskBit], value: u64) -> u64 {
     (0..BITS).fold(0, |acc, i| {
         let v = match mask[i] {
-            b'0' => 0,
-            b'1' => 1,
-            b'X' => (value >> BITS - i - 1) & 1,
-            other => panic!("illegal mask entry: {}", other),
+            MaskBit::Zero => 0,
+            MaskBit::One => 1,
+            MaskBit::Floating => (value >> (BITS - i - 1)) & 1,
         };
         2 * acc + v
     })
 }
 
-fn solve2(ops: Vec<Op>) -> HashMap<u64, u64> {
+/// Memory after running with the mask applied to (floating) addresses.
+pub fn solve2(ops: &[Op]) -> HashMap<u64, u64> {
     let mut registers = HashMap::new();
-    let mut mask = vec![0; BITS];
+    let mut mask: &[MaskBit] = &[MaskBit::Zero; BITS];
     for op in ops {
         match op {
             Op::SetMask(m) => {
                 mask = m;
             }
             Op::Write { addr, value } => {
-                for a in mask_addr(&mask, addr) {
-                    registers.insert(a, value);
+                for a in mask_addr(mask, *addr) {
+                    registers.insert(a, *value);
                 }
             }
         };
@@ -52,14 +107,13 @@ fn solve2(ops: Vec<Op>) -> HashMap<u64, u64> {
     registers
 }
 
-fn mask_addr(mask: &[u8], addr: u64) -> Vec<u64> {
+fn mask_addr(mask: &[MaskBit], addr: u64) -> Vec<u64> {
     let mut addrs = vec![0];
-    for i in 0..BITS {
-        let vs = match mask[i] {
-            b'0' => vec![(addr >> BITS - i - 1) & 1],
-            b'1' => vec![1],
-            b'X' => vec![0, 1],
-            other => panic!("illegal mask entry: {}", other),
+    for (i, m) in mask.iter().enumerate().take(BITS) {
+        let vs = match m {
+            MaskBit::Zero => vec![(addr >> (BITS - i - 1)) & 1],
+            MaskBit::One => vec![1],
+            MaskBit::Floating => vec![0, 1],
         };
         let mut next = Vec::with_capacity(addrs.len() * vs.len());
         for &a in &addrs {
@@ -72,35 +126,56 @@ fn mask_addr(mask: &[u8], addr: u64) -> Vec<u64> {
     addrs
 }
 
+/// [`Solution`] for day 14.
+pub struct Day14;
+impl Solution for Day14 {
+    type Input = Vec<Op>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<u64> {
+        Some(solve1(input).values().sum())
+    }
+    fn part2(input: &Self::Input) -> Option<u64> {
+        Some(solve2(input).values().sum())
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{solve1, solve2, Op};
-    use lazy_static::lazy_static;
-    use regex::Regex;
+    use super::{parse, solve1, solve2};
 
-    lazy_static! {
-        static ref MASK: Regex = Regex::new(r"^mask = ([01X]{36})$").unwrap();
-        static ref WRITE: Regex = Regex::new(r"^mem\[([[:digit:]]+)\] = ([[:digit:]]+)$").unwrap();
+    #[test]
+    fn parser() {
+        assert_eq!(parse("mem[8] = 11").unwrap().len(), 1);
+        assert!(parse("mask = 0X1").is_err());
+        assert_eq!(
+            parse("mask = 0X1\nmem[99999999999999999999] = 1")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 14, line 1, column 1: expected `mask = <36 of 0, 1 or X>` or \
+             `mem[<addr>] = <value>`, found `mask`"
+        );
+        assert_eq!(
+            parse("mem[99999999999999999999] = 1")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 14, line 1, column 5: expected a 64-bit number, found `99999999999999999999]`"
+        );
     }
-    fn parse(input: &str) -> Vec<Op> {
-        input
-            .trim()
-            .lines()
-            .map(|l| {
-                let s = l.trim();
-                if let Some(m) = MASK.captures(s) {
-                    let mask = m.get(1).unwrap().as_str();
-                    Op::SetMask(mask.to_owned().into_bytes())
-                } else if let Some(m) = WRITE.captures(s) {
-                    let addr = m.get(1).unwrap().as_str().parse().unwrap();
-                    let value = m.get(2).unwrap().as_str().parse().unwrap();
-                    Op::Write { addr, value }
-                } else {
-                    panic!("invalid line: {}", s);
-                }
-            })
-            .collect()
+
+    #[test]
+    fn unmasked() {
+        let input = parse("mem[8] = 11").unwrap();
+        assert_eq!(solve1(&input).get(&8), Some(&11));
+        assert_eq!(solve2(&input).get(&8), Some(&11));
     }
+
     #[test]
     fn small1() {
         let raw = r"
@@ -109,15 +184,8 @@ mod test {
             mem[7] = 101
             mem[8] = 0
         ";
-        let input = parse(raw);
-        assert_eq!(solve1(input).values().sum::<u64>(), 165);
-    }
-
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day14.input").unwrap();
-        let input = parse(&raw);
-        assert_eq!(solve1(input).values().sum::<u64>(), 16003257187056);
+        let input = parse(raw).unwrap();
+        assert_eq!(solve1(&input).values().sum::<u64>(), 165);
     }
 
     #[test]
@@ -128,14 +196,7 @@ mod test {
             mask = 00000000000000000000000000000000X0XX
             mem[26] = 1
         ";
-        let input = parse(raw);
-        assert_eq!(solve2(input).values().sum::<u64>(), 208);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day14.input").unwrap();
-        let input = parse(&raw);
-        assert_eq!(solve2(input).values().sum::<u64>(), 3219837697833);
+        let input = parse(raw).unwrap();
+        assert_eq!(solve2(&input).values().sum::<u64>(), 208);
     }
 }
@@ -1,6 +1,24 @@
-fn solve(input: &str, n: usize) -> usize {
-    let xs: Vec<usize> = input.split(',').map(|s| s.parse().unwrap()).collect();
-    let mut d = vec![0; n];
+//! Day 15: Rambunctious Recitation.
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 15;
+
+/// Parses the comma-separated starting numbers.
+pub fn parse(input: &str) -> Result<Vec<usize>, Error> {
+    input
+        .trim()
+        .split(',')
+        .map(|s| {
+            s.parse()
+                .map_err(|_| Error::parse(DAY, input, s, "a starting number"))
+        })
+        .collect()
+}
+
+/// The `n`th number spoken in the memory game.
+pub fn solve(xs: &[usize], n: usize) -> usize {
+    let mut d = vec![0; n.max(xs.iter().max().map_or(0, |&x| x + 1))];
     for (idx, &x) in xs.iter().enumerate() {
         d[x] = idx + 1;
     }
@@ -13,22 +31,40 @@ fn solve(input: &str, n: usize) -> usize {
     cur
 }
 
-#[cfg(test)]
-mod test {
-    use super::solve;
+/// [`Solution`] for day 15.
+pub struct Day15;
+impl Solution for Day15 {
+    type Input = Vec<usize>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    #[test]
-    fn small1() {
-        assert_eq!(solve("0,3,6", 2020), 436);
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(solve(input, 2020))
     }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(solve(input, 30_000_000))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, solve};
 
     #[test]
-    fn normal1() {
-        assert_eq!(solve("1,20,11,6,12,0", 2020), 1085);
+    fn parser() {
+        assert_eq!(parse("0,3,6\n").unwrap(), vec![0, 3, 6]);
+        assert_eq!(
+            parse("0,-3,6").unwrap_err().to_string(),
+            "day 15, line 1, column 3: expected a starting number, found `-3,6`"
+        );
+        assert_eq!(solve(&parse("2021").unwrap(), 1), 2021);
     }
 
     #[test]
-    fn normal2() {
-        assert_eq!(solve("1,20,11,6,12,0", 30_000_000), 10652);
+    fn small1() {
+        assert_eq!(solve(&parse("0,3,6").unwrap(), 2020), 436);
     }
 }
@@ -1,3 +1,5 @@
+//! Day 16: Ticket Translation.
+
 use std::{
     collections::{HashMap, HashSet},
     ops::RangeInclusive,
@@ -14,31 +16,39 @@ use nom::{
     IResult,
 };
 
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 16;
+
+/// A ticket field and the ranges its value may fall in.
 #[derive(Debug, PartialEq, Eq, Clone)]
-struct FieldSpec {
-    name: String,
-    ranges: Vec<RangeInclusive<usize>>,
+pub struct FieldSpec {
+    pub name: String,
+    pub ranges: Vec<RangeInclusive<usize>>,
 }
 impl FieldSpec {
-    fn allows(&self, v: usize) -> bool {
+    pub fn allows(&self, v: usize) -> bool {
         self.ranges.iter().any(|r| r.contains(&v))
     }
 }
 
-type Ticket = Vec<usize>;
-struct Input {
-    specs: Vec<FieldSpec>,
-    my_ticket: Ticket,
-    nearby_tickets: Vec<Ticket>,
+/// Field values in the order they appear on a ticket.
+pub type Ticket = Vec<usize>;
+/// The notes: field rules, your ticket and nearby tickets.
+pub struct Input {
+    pub specs: Vec<FieldSpec>,
+    pub my_ticket: Ticket,
+    pub nearby_tickets: Vec<Ticket>,
 }
 
 impl FromStr for Input {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        all_consuming(input_parser)(s)
+        let expected = "field rules, then `your ticket:` and `nearby tickets:` sections";
+        all_consuming(delimited(multispace0, input_parser, multispace0))(s)
             .map(|(_, parsed)| parsed)
-            .map_err(|_| s.to_owned())
+            .map_err(|e| Error::nom(DAY, s, e, expected))
     }
 }
 fn input_parser(input: &str) -> IResult<&str, Input> {
@@ -77,10 +87,13 @@ fn range_parser(input: &str) -> IResult<&str, RangeInclusive<usize>> {
     Ok((input, lo..=hi))
 }
 fn usize_parser(input: &str) -> IResult<&str, usize> {
-    map_res(take_while1(|c: char| c.is_digit(10)), |s: &str| s.parse())(input)
+    map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
+        s.parse()
+    })(input)
 }
 
-fn solve1(input: &Input) -> usize {
+/// Ticket scanning error rate: the sum of values that fit no field.
+pub fn solve1(input: &Input) -> usize {
     let is_valid = |v| input.specs.iter().any(|spec| spec.allows(v));
     input
         .nearby_tickets
@@ -89,8 +102,11 @@ fn solve1(input: &Input) -> usize {
         .sum()
 }
 
-type LabeledTicket = Vec<String>;
-fn solve2(input: &Input) -> LabeledTicket {
+/// Field names in ticket order.
+pub type LabeledTicket = Vec<String>;
+/// Works out which field is which from the valid nearby tickets; `None` if
+/// they do not pin it down.
+pub fn solve2(input: &Input) -> Option<LabeledTicket> {
     let is_valid = |v| input.specs.iter().any(|spec| spec.allows(v));
     let valid_tickets: Vec<Ticket> = input
         .nearby_tickets
@@ -123,10 +139,7 @@ fn solve2(input: &Input) -> LabeledTicket {
     let mut mapping: HashMap<usize, String> = HashMap::new();
     while mapping.len() < ticket_size {
         // Find a name with an obvious mapping.
-        let trivial: &Possibility = possibilities
-            .iter()
-            .find(|p| p.candidates.len() == 1)
-            .unwrap();
+        let trivial: &Possibility = possibilities.iter().find(|p| p.candidates.len() == 1)?;
         let idx: usize = *trivial.candidates.iter().next().unwrap();
         mapping.insert(idx, trivial.field.name.clone());
         // No other field is allowed to use this index now.
@@ -134,12 +147,40 @@ fn solve2(input: &Input) -> LabeledTicket {
             p.candidates.remove(&idx);
         }
     }
-    (0..ticket_size).map(|idx| mapping[&idx].clone()).collect()
+    (0..ticket_size).map(|idx| mapping.remove(&idx)).collect()
+}
+
+/// [`Solution`] for day 16.
+pub struct Day16;
+impl Solution for Day16 {
+    type Input = Input;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        input.parse()
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(solve1(input))
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        let labels = solve2(input)?;
+        Some(
+            input
+                .my_ticket
+                .iter()
+                .zip(labels)
+                .filter(|(_, name)| name.starts_with("departure"))
+                .map(|(v, _)| v)
+                .product(),
+        )
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::{field_parser, range_parser, solve1, solve2, FieldSpec, Input};
+    use crate::error::Error;
 
     const SMALL: &str = r"
         class: 1-3 or 5-7
@@ -166,6 +207,18 @@ mod test {
                 ranges: vec![3..=7, 9..=12]
             }
         );
+        let bad = "class: 1-3\n\nyour ticket:\n7\n\nnearby tickets:\n7\n40;50";
+        assert_eq!(
+            bad.parse::<Input>().err().unwrap(),
+            Error::Parse {
+                day: 16,
+                line: 8,
+                column: 3,
+                expected: "field rules, then `your ticket:` and `nearby tickets:` sections"
+                    .to_owned(),
+                found: "`;50`".to_owned(),
+            }
+        );
     }
 
     #[test]
@@ -174,31 +227,21 @@ mod test {
         assert_eq!(solve1(&input), 71);
     }
 
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day16.input").unwrap();
-        let input = raw.trim().parse::<Input>().unwrap();
-        assert_eq!(solve1(&input), 18142);
-    }
-
     #[test]
     fn small2() {
         let input = SMALL.trim().parse::<Input>().unwrap();
-        assert_eq!(solve2(&input), vec!["row", "class", "seat"]);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day16.input").unwrap();
-        let input = raw.trim().parse::<Input>().unwrap();
-        let labels = solve2(&input);
-        let computed = input
-            .my_ticket
-            .into_iter()
-            .zip(labels.into_iter())
-            .filter(|(_, name)| name.starts_with("departure"))
-            .map(|(v, _)| v)
-            .product::<usize>();
-        assert_eq!(computed, 1069784384303);
+        assert_eq!(
+            solve2(&input),
+            Some(vec![
+                "row".to_owned(),
+                "class".to_owned(),
+                "seat".to_owned()
+            ])
+        );
+        // Every value fits both fields, so either could come first.
+        let input = "a: 1-9 or 20-29\nb: 1-9 or 20-29\n\nyour ticket:\n1,2\n\nnearby tickets:\n3,4"
+            .parse::<Input>()
+            .unwrap();
+        assert_eq!(solve2(&input), None);
     }
 }
@@ -1,108 +1,288 @@
-use std::collections::{HashMap, HashSet};
+//! Day 17: Conway Cubes.
+
+use std::collections::HashSet;
 use std::hash::Hash;
 
-trait Cell: Eq + PartialEq + Sized + Hash {
+use crate::{
+    automaton::{Automaton, LifeRule, Orbit, Sparse, Symmetric},
+    error::Error,
+    grid::Grid,
+    recorder::{Draw, Layer},
+    solution::Solution,
+};
+
+const DAY: u8 = 17;
+
+/// A point in a Conway cube universe that can be seeded from a 2-D slice.
+/// Its [`Orbit`] groups the points that a universe seeded from a slice
+/// always treats alike.
+pub trait Cell: Orbit + Eq + Sized + Hash + Clone {
     fn of(i: i32, j: i32) -> Self;
+    /// Every adjacent cell, diagonals included, but not `self`.
     fn neighbors(&self) -> Vec<Self>;
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct P3(i32, i32, i32);
-impl Cell for P3 {
-    fn of(i: i32, j: i32) -> P3 {
-        P3(i, j, 0)
-    }
-    fn neighbors(&self) -> Vec<P3> {
-        let mut ns = Vec::with_capacity(27);
-        let P3(i, j, k) = self;
-        for di in -1..=1 {
-            for dj in -1..=1 {
-                for dk in -1..=1 {
-                    ns.push(P3(i + di, j + dj, k + dk))
+/// A point in `N >= 2` dimensions; the initial slice lies along the first
+/// two axes.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct P<const N: usize>(pub [i32; N]);
+/// A point in three dimensions.
+pub type P3 = P<3>;
+/// A point in four dimensions.
+pub type P4 = P<4>;
+impl<const N: usize> P<N> {
+    // Naming this fails the build for points in fewer than two dimensions.
+    const AT_LEAST_2D: () = assert!(N >= 2, "a point needs at least two dimensions");
+}
+impl<const N: usize> Cell for P<N> {
+    fn of(i: i32, j: i32) -> P<N> {
+        let () = P::<N>::AT_LEAST_2D;
+        let mut p = [0; N];
+        p[0] = i;
+        p[1] = j;
+        P(p)
+    }
+    fn neighbors(&self) -> Vec<P<N>> {
+        // Each offset in -1..=1 along every axis is a base-3 digit of `k`;
+        // all 1s (the middle `k`) is `self`.
+        let count = 3usize.pow(N as u32);
+        (0..count)
+            .filter(|&k| k != count / 2)
+            .map(|mut k| {
+                let mut p = self.0;
+                for x in &mut p {
+                    *x += (k % 3) as i32 - 1;
+                    k /= 3;
                 }
-            }
-        }
-        ns
+                P(p)
+            })
+            .collect()
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct P4(i32, i32, i32, i32);
-impl Cell for P4 {
-    fn of(i: i32, j: i32) -> P4 {
-        P4(i, j, 0, 0)
-    }
-    fn neighbors(&self) -> Vec<P4> {
-        let mut ns = Vec::with_capacity(81);
-        let P4(i, j, k, l) = self;
-        for di in -1..=1 {
-            for dj in -1..=1 {
-                for dk in -1..=1 {
-                    for dl in -1..=1 {
-                        ns.push(P4(i + di, j + dj, k + dk, l + dl))
-                    }
-                }
-            }
+/// A universe seeded from a slice is symmetric in the axes beyond the
+/// first two: flipping the sign of any of them, or swapping any two, changes
+/// nothing.
+impl<const N: usize> Orbit for P<N> {
+    fn canonical(&self) -> P<N> {
+        let () = P::<N>::AT_LEAST_2D;
+        let mut p = self.0;
+        for x in &mut p[2..] {
+            *x = x.abs();
         }
-        ns
+        p[2..].sort_unstable();
+        P(p)
+    }
+    fn weight(&self) -> u64 {
+        let () = P::<N>::AT_LEAST_2D;
+        // The distinct orderings of the extra axes, times a sign for each
+        // that is not 0.
+        let mut extra: Vec<i32> = self.0[2..].iter().map(|x| x.abs()).collect();
+        extra.sort_unstable();
+        let factorial = |n: usize| (1..=n as u64).product::<u64>();
+        let repeats: u64 = extra
+            .chunk_by(|a, b| a == b)
+            .map(|run| factorial(run.len()))
+            .product();
+        let signs = extra.iter().filter(|&&x| x != 0).count();
+        (factorial(extra.len()) / repeats) << signs
     }
 }
 
-struct Universe<C: Cell> {
+/// The rule the pocket dimension follows.
+pub const RULE: &str = "B3/S23";
+
+/// The set of active cubes.
+pub struct Universe<C: Cell> {
     active: HashSet<C>,
+    rule: LifeRule,
+    // Whether `active` only holds canonical cells.
+    reduced: bool,
 }
 impl<C: Cell> Universe<C> {
-    fn count_active(&self) -> usize {
-        self.active.len()
-    }
-    fn step(&mut self) {
-        let mut adj: HashMap<C, usize> = HashMap::new();
-        for p in &self.active {
-            for n in p.neighbors() {
-                if n != *p {
-                    *adj.entry(n).or_default() += 1;
-                }
-            }
+    /// Seeds a universe from the active cells of a 2-D slice, following
+    /// [`RULE`].
+    pub fn from_slice(active: &[(i32, i32)]) -> Universe<C> {
+        Universe {
+            active: active.iter().map(|&(i, j)| C::of(i, j)).collect(),
+            rule: RULE.parse().unwrap(),
+            reduced: false,
         }
-        let mut next = HashSet::new();
-        for (p, c) in adj {
-            if c == 3 || (c == 2 && self.active.contains(&p)) {
-                next.insert(p);
-            }
+    }
+    /// The same universe, storing one cell per [`Orbit`]. Much faster in
+    /// higher dimensions, with the same results.
+    pub fn reduced(self) -> Universe<C> {
+        Universe {
+            active: self.active.iter().map(Orbit::canonical).collect(),
+            reduced: true,
+            ..self
+        }
+    }
+    /// The same universe, following `rule` from now on.
+    pub fn with_rule(self, rule: LifeRule) -> Universe<C> {
+        Universe { rule, ..self }
+    }
+    pub fn is_active(&self, cell: &C) -> bool {
+        if self.reduced {
+            self.active.contains(&cell.canonical())
+        } else {
+            self.active.contains(cell)
+        }
+    }
+    pub fn count_active(&self) -> usize {
+        if self.reduced {
+            self.active.iter().map(|c| c.weight() as usize).sum()
+        } else {
+            self.active.len()
         }
-        std::mem::swap(&mut self.active, &mut next);
+    }
+    /// Runs a single boot cycle.
+    pub fn step(&mut self) {
+        self.active = if self.reduced {
+            Automaton::new(Symmetric::new(C::neighbors), self.rule).step(&self.active)
+        } else {
+            Automaton::new(Sparse::new(C::neighbors), self.rule).step(&self.active)
+        };
     }
 }
 
-fn parse_grid<C: Cell>(raw: &str) -> Universe<C> {
-    let mut active = HashSet::new();
-    for (i, line) in raw.trim().lines().enumerate() {
-        for (j, ch) in line.trim().chars().enumerate() {
-            if ch == '#' {
-                active.insert(C::of(i as i32, j as i32));
+/// One layer per slice through the axes beyond the first two, labelled like
+/// `z=-1, w=0`, each spanning every active cube.
+impl<const N: usize> Draw for Universe<P<N>> {
+    fn draw(&self) -> Vec<Layer> {
+        let () = P::<N>::AT_LEAST_2D;
+        if self.active.is_empty() {
+            return Vec::new();
+        }
+        let (mut lo, mut hi) = ([i32::MAX; N], [i32::MIN; N]);
+        for P(p) in &self.active {
+            for axis in 0..N {
+                lo[axis] = lo[axis].min(p[axis]);
+                hi[axis] = hi[axis].max(p[axis]);
+            }
+        }
+        if self.reduced {
+            // Canonical cubes only show one mirror image of the extra axes.
+            let reach = hi[2..].iter().copied().max().unwrap_or(0);
+            for axis in 2..N {
+                lo[axis] = -reach;
+                hi[axis] = reach;
             }
         }
+
+        let mut slices: Vec<Vec<i32>> = vec![Vec::new()];
+        for axis in 2..N {
+            slices = slices
+                .into_iter()
+                .flat_map(|slice| {
+                    (lo[axis]..=hi[axis]).map(move |x| {
+                        let mut slice = slice.clone();
+                        slice.push(x);
+                        slice
+                    })
+                })
+                .collect();
+        }
+        let width = (hi[1] - lo[1] + 1) as usize;
+        let height = (hi[0] - lo[0] + 1) as usize;
+        slices
+            .into_iter()
+            .map(|extra| {
+                let label = extra
+                    .iter()
+                    .enumerate()
+                    .map(|(k, x)| format!("{}={}", axis_name(k + 2), x))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let grid = Grid::from_fn(width, height, |(i, j)| {
+                    let mut p = [0; N];
+                    p[0] = lo[0] + i;
+                    p[1] = lo[1] + j;
+                    p[2..].copy_from_slice(&extra);
+                    if self.is_active(&P(p)) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                });
+                Layer { label, grid }
+            })
+            .collect()
     }
-    Universe { active }
 }
 
-fn solve1(mut init: Universe<P3>) -> usize {
-    for _ in 0..6 {
-        init.step();
+// `z`, then `w`, then backwards through the alphabet, then numbered like
+// `x26`.
+fn axis_name(axis: usize) -> String {
+    match axis {
+        2 => "z".to_owned(),
+        3..=25 => ((b'w' - (axis - 3) as u8) as char).to_string(),
+        _ => format!("x{}", axis),
     }
-    init.count_active()
 }
 
-fn solve2(mut init: Universe<P4>) -> usize {
-    for _ in 0..6 {
+/// Parses the initial slice, returning the `(row, column)` of each active cube.
+pub fn parse_slice(raw: &str) -> Result<Vec<(i32, i32)>, Error> {
+    let slice = Grid::parse(DAY, raw, "`#` or `.`", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    Ok(slice
+        .iter()
+        .filter(|&(_, &active)| active)
+        .map(|(pos, _)| pos)
+        .collect())
+}
+
+/// Parses the initial slice straight into a universe.
+pub fn parse_grid<C: Cell>(raw: &str) -> Result<Universe<C>, Error> {
+    Ok(Universe::from_slice(&parse_slice(raw)?))
+}
+
+/// Active cubes after `cycles` boot cycles.
+pub fn boot<C: Cell>(mut init: Universe<C>, cycles: usize) -> usize {
+    for _ in 0..cycles {
         init.step();
     }
     init.count_active()
 }
 
+/// Active cubes after six cycles in three dimensions.
+pub fn solve1(init: Universe<P3>) -> usize {
+    boot(init, 6)
+}
+
+/// Active cubes after six cycles in four dimensions.
+pub fn solve2(init: Universe<P4>) -> usize {
+    boot(init, 6)
+}
+
+/// [`Solution`] for day 17.
+pub struct Day17;
+impl Solution for Day17 {
+    type Input = Vec<(i32, i32)>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_slice(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(solve1(Universe::from_slice(input)))
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(solve2(Universe::from_slice(input).reduced()))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{parse_grid, solve1, solve2, P3};
+    use super::{axis_name, boot, parse_grid, solve1, solve2, Cell, P, P3, P4};
+    use crate::{
+        automaton::Orbit,
+        recorder::{Draw, Recorder},
+    };
 
     const SMALL: &str = r"
         .#.
@@ -112,28 +292,84 @@ mod test {
 
     #[test]
     fn parser() {
-        assert_eq!(parse_grid::<P3>(SMALL).count_active(), 5);
+        assert_eq!(parse_grid::<P3>(SMALL).unwrap().count_active(), 5);
+        assert_eq!(
+            parse_grid::<P3>(".#.\n.o.").err().unwrap().to_string(),
+            "day 17, line 2, column 2: expected `#` or `.`, found `o.`"
+        );
     }
 
     #[test]
     fn small1() {
-        assert_eq!(solve1(parse_grid(SMALL)), 112);
+        assert_eq!(solve1(parse_grid(SMALL).unwrap()), 112);
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day17.input").unwrap();
-        assert_eq!(solve1(parse_grid(&raw)), 353);
+    fn small2() {
+        assert_eq!(solve2(parse_grid(SMALL).unwrap()), 848);
     }
 
     #[test]
-    fn small2() {
-        assert_eq!(solve2(parse_grid(SMALL)), 848);
+    fn rules() {
+        // With no births and every count surviving, the seed is frozen.
+        let mut universe = parse_grid::<P4>(SMALL)
+            .unwrap()
+            .with_rule("B/S012345678".parse().unwrap());
+        universe.step();
+        assert_eq!(universe.count_active(), 5);
+        // With no survivors, it dies out.
+        let mut universe = universe.with_rule("B/S".parse().unwrap());
+        universe.step();
+        assert_eq!(universe.count_active(), 0);
+    }
+
+    #[test]
+    fn dimensions() {
+        let neighbors = P::<5>::of(1, 2).neighbors();
+        assert_eq!(neighbors.len(), 242);
+        assert!(neighbors.contains(&P([0, 3, -1, 1, 0])));
+        assert!(!neighbors.contains(&P([1, 2, 0, 0, 0])));
+        // In two dimensions the slice is a glider, which keeps its 5 cells.
+        assert_eq!(boot(parse_grid::<P<2>>(SMALL).unwrap(), 6), 5);
+        assert_eq!(boot(parse_grid::<P<3>>(SMALL).unwrap(), 1), 11);
+    }
+
+    #[test]
+    fn reduced() {
+        assert_eq!(P([5, 6, -2, 0, 2, 1]).canonical(), P([5, 6, 0, 1, 2, 2]));
+        // 4!/2! orderings of {0, 1, 2, 2}, times 2^3 signs.
+        assert_eq!(P([5, 6, -2, 0, 2, 1]).weight(), 96);
+        assert_eq!(P([5, 6]).weight(), 1);
+        assert_eq!(solve2(parse_grid(SMALL).unwrap().reduced()), 848);
+        for cycles in 0..4 {
+            let full = boot(parse_grid::<P<5>>(SMALL).unwrap(), cycles);
+            let reduced = boot(parse_grid::<P<5>>(SMALL).unwrap().reduced(), cycles);
+            assert_eq!(full, reduced);
+        }
     }
 
     #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day17.input").unwrap();
-        assert_eq!(solve2(parse_grid(&raw)), 2472);
+    fn draw() {
+        let mut full = parse_grid::<P3>(SMALL).unwrap();
+        let mut reduced = parse_grid::<P3>(SMALL).unwrap().reduced();
+        full.step();
+        reduced.step();
+        let mut recorder = Recorder::new();
+        recorder.record(&full);
+        recorder.record(&reduced);
+        let cycle1 = "\nz=-1\n#..\n..#\n.#.\n\nz=0\n#.#\n.##\n.#.\n\nz=1\n#..\n..#\n.#.\n";
+        assert_eq!(
+            recorder.text(),
+            format!("Generation 0:\n{}\nGeneration 1:\n{}", cycle1, cycle1)
+        );
+        let layers = parse_grid::<P<4>>(SMALL).unwrap().draw();
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].label, "z=0, w=0");
+        assert_eq!(parse_grid::<P<2>>(SMALL).unwrap().draw()[0].label, "");
+        let names: Vec<String> = [2, 3, 4, 25, 26, 30]
+            .iter()
+            .map(|&a| axis_name(a))
+            .collect();
+        assert_eq!(names, ["z", "w", "v", "a", "x26", "x30"]);
     }
 }
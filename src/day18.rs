@@ -1,16 +1,23 @@
+//! Day 18: Operation Order.
+
 use nom::{
     branch::alt,
     bytes::complete::tag,
     bytes::complete::take_while1,
     character::complete::{multispace0, one_of},
-    combinator::{map, map_res},
+    combinator::{all_consuming, cut, map, map_res},
     multi::fold_many0,
     sequence::{delimited, pair, preceded},
     IResult,
 };
 
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 18;
+
+/// An arithmetic expression tree.
 #[derive(Debug, PartialEq, Eq, Clone)]
-enum Expr {
+pub enum Expr {
     Literal(i64),
     Add(Box<Expr>, Box<Expr>),
     Multiply(Box<Expr>, Box<Expr>),
@@ -44,7 +51,7 @@ fn lr_term_parser(input: &str) -> IResult<&str, Expr> {
     )(input)
 }
 fn lr_paren_parser(input: &str) -> IResult<&str, Expr> {
-    delimited(tag("("), lr_parser, tag(")"))(input)
+    delimited(tag("("), cut(lr_parser), cut(tag(")")))(input)
 }
 
 fn mul_parser(input: &str) -> IResult<&str, Expr> {
@@ -52,7 +59,7 @@ fn mul_parser(input: &str) -> IResult<&str, Expr> {
     fold_many0(
         preceded(tag("*"), add_parser),
         move || t0.clone(),
-        |acc, term| Expr::mul(acc, term),
+        Expr::mul,
     )(input)
 }
 fn add_parser(input: &str) -> IResult<&str, Expr> {
@@ -60,7 +67,7 @@ fn add_parser(input: &str) -> IResult<&str, Expr> {
     fold_many0(
         preceded(tag("+"), mul_term_parser),
         move || t0.clone(),
-        |acc, term| Expr::add(acc, term),
+        Expr::add,
     )(input)
 }
 fn mul_term_parser(input: &str) -> IResult<&str, Expr> {
@@ -71,49 +78,85 @@ fn mul_term_parser(input: &str) -> IResult<&str, Expr> {
     )(input)
 }
 fn mul_paren_parser(input: &str) -> IResult<&str, Expr> {
-    delimited(tag("("), mul_parser, tag(")"))(input)
+    delimited(tag("("), cut(mul_parser), cut(tag(")")))(input)
 }
 
 fn literal_parser(input: &str) -> IResult<&str, Expr> {
-    map(int_parser, |n| Expr::Literal(n))(input)
+    map(int_parser, Expr::Literal)(input)
 }
 fn int_parser(input: &str) -> IResult<&str, i64> {
-    map_res(take_while1(|c: char| c.is_digit(10)), |s: &str| s.parse())(input)
+    map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
+        s.parse()
+    })(input)
 }
 
-fn evaluate(expr: Expr) -> i64 {
-    match expr {
-        Expr::Literal(v) => v,
-        Expr::Add(a, b) => evaluate(*a) + evaluate(*b),
-        Expr::Multiply(a, b) => evaluate(*a) * evaluate(*b),
-    }
+/// Which operator precedence rules to parse with.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Precedence {
+    /// `+` and `*` bind equally tightly, evaluated left to right.
+    LeftToRight,
+    /// `+` binds tighter than `*`.
+    AdditionFirst,
 }
 
-fn solve1(input: &str) -> i64 {
+/// Parses one expression per line.
+pub fn parse(input: &str, precedence: Precedence) -> Result<Vec<Expr>, Error> {
+    let parser = match precedence {
+        Precedence::LeftToRight => lr_parser,
+        Precedence::AdditionFirst => mul_parser,
+    };
     input
-        .trim()
         .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
         .map(|line| {
-            let expr = lr_parser(line.trim()).unwrap().1;
-            evaluate(expr)
+            all_consuming(parser)(line)
+                .map(|(_, expr)| expr)
+                .map_err(|e| Error::nom(DAY, input, e, "a number, `(`, `)`, `+` or `*`"))
         })
-        .sum()
+        .collect()
 }
 
-fn solve2(input: &str) -> i64 {
-    input
-        .trim()
-        .lines()
-        .map(|line| {
-            let expr = mul_parser(line.trim()).unwrap().1;
-            evaluate(expr)
-        })
-        .sum()
+/// The value of an expression.
+pub fn evaluate(expr: &Expr) -> i64 {
+    match expr {
+        Expr::Literal(v) => *v,
+        Expr::Add(a, b) => evaluate(a) + evaluate(b),
+        Expr::Multiply(a, b) => evaluate(a) * evaluate(b),
+    }
+}
+
+/// Sum of every expression.
+pub fn solve(exprs: &[Expr]) -> i64 {
+    exprs.iter().map(evaluate).sum()
+}
+
+/// [`Solution`] for day 18.
+pub struct Day18;
+impl Solution for Day18 {
+    /// The homework parsed under [`Precedence::LeftToRight`] and
+    /// [`Precedence::AdditionFirst`].
+    type Input = (Vec<Expr>, Vec<Expr>);
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        Ok((
+            parse(input, Precedence::LeftToRight)?,
+            parse(input, Precedence::AdditionFirst)?,
+        ))
+    }
+    fn part1(input: &Self::Input) -> Option<i64> {
+        Some(solve(&input.0))
+    }
+    fn part2(input: &Self::Input) -> Option<i64> {
+        Some(solve(&input.1))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{lr_parser, solve1, solve2, Expr};
+    use super::{lr_parser, parse, solve, Expr, Precedence};
     #[test]
     fn parser() {
         assert_eq!(lr_parser("3").unwrap().1, Expr::Literal(3));
@@ -139,6 +182,12 @@ mod test {
                 Expr::mul(Expr::Literal(4), Expr::Literal(5))
             )
         );
+        assert_eq!(
+            parse("1 + 2\n3 * (4 + 5", Precedence::AdditionFirst)
+                .unwrap_err()
+                .to_string(),
+            "day 18, line 2, column 11: expected a number, `(`, `)`, `+` or `*`, found end of input"
+        );
     }
 
     #[test]
@@ -149,18 +198,6 @@ mod test {
             5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
             ((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2
         ";
-        assert_eq!(solve1(raw), 26335);
-    }
-
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day18.input").unwrap();
-        assert_eq!(solve1(&raw), 25190263477788);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day18.input").unwrap();
-        assert_eq!(solve2(&raw), 297139939002972);
+        assert_eq!(solve(&parse(raw, Precedence::LeftToRight).unwrap()), 26335);
     }
 }
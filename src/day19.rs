@@ -1,3 +1,5 @@
+//! Day 19: Monster Messages.
+
 use std::collections::HashMap;
 
 use nom::{
@@ -5,36 +7,46 @@ use nom::{
     bytes::complete::tag,
     bytes::complete::take_while1,
     character::complete::{anychar, space1},
-    combinator::{map, map_res},
+    combinator::{all_consuming, map, map_res},
     multi::separated_list1,
     sequence::{delimited, separated_pair},
     IResult,
 };
 
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 19;
+
+/// The right-hand side of a grammar rule.
 #[derive(Debug, PartialEq, Eq, Clone)]
-enum Rule {
+pub enum Rule {
     Literal(char),
     Subrule(Vec<usize>),
     Alt(Vec<usize>, Vec<usize>),
 }
 
-struct Grammar {
-    rules: HashMap<usize, Rule>,
+#[derive(Debug, Clone)]
+/// Numbered rules; messages are matched against rule 0.
+pub struct Grammar {
+    pub rules: HashMap<usize, Rule>,
 }
 impl Grammar {
-    fn accepts<'a>(&self, input: &'a str) -> bool {
+    pub fn accepts(&self, input: &str) -> bool {
         self.check(vec![0], input)
     }
-    fn check<'a>(&self, mut rules: Vec<usize>, input: &str) -> bool {
+    fn check(&self, mut rules: Vec<usize>, input: &str) -> bool {
         if rules.is_empty() && input.is_empty() {
             return true;
         }
-        let first_rule = match rules.pop() {
-            Some(id) => &self.rules[&id],
+        let first_rule = match rules.pop().and_then(|id| self.rules.get(&id)) {
+            Some(rule) => rule,
             None => return false,
         };
         match first_rule {
-            Rule::Literal(c) => input.starts_with(*c) && self.check(rules, &input[1..]),
+            Rule::Literal(c) => match input.strip_prefix(*c) {
+                Some(rest) => self.check(rules, rest),
+                None => false,
+            },
             Rule::Subrule(sub) => {
                 rules.extend(sub.iter().rev());
                 self.check(rules, input)
@@ -59,12 +71,12 @@ fn rule_parser(input: &str) -> IResult<&str, (usize, Rule)> {
     )(input)
 }
 fn subrule_parser(input: &str) -> IResult<&str, Rule> {
-    map(
+    map_res(
         separated_list1(tag(" | "), separated_list1(space1, usize_parser)),
-        |vs| match vs.len() {
-            1 => Rule::Subrule(vs[0].clone()),
-            2 => Rule::Alt(vs[0].clone(), vs[1].clone()),
-            _ => panic!(),
+        |mut vs| match vs.len() {
+            1 => Ok(Rule::Subrule(vs.remove(0))),
+            2 => Ok(Rule::Alt(vs.remove(0), vs.remove(0))),
+            _ => Err(()),
         },
     )(input)
 }
@@ -77,42 +89,88 @@ fn u8_parser(input: &str) -> IResult<&str, char> {
     anychar(input)
 }
 fn usize_parser(input: &str) -> IResult<&str, usize> {
-    map_res(take_while1(|c: char| c.is_digit(10)), |s: &str| s.parse())(input)
+    map_res(take_while1(|c: char| c.is_ascii_digit()), |s: &str| {
+        s.parse()
+    })(input)
 }
 
-fn parse_grammar(input: &str) -> (Grammar, Vec<&str>) {
+/// Parses the rules, then the messages after the blank line.
+pub fn parse_grammar(input: &str) -> Result<(Grammar, Vec<String>), Error> {
     let mut lines = input.trim().lines().map(|l| l.trim());
     let mut rules = HashMap::new();
-    while let Some(l) = lines.next() {
+    let mut sources = Vec::new();
+    for l in lines.by_ref() {
         if l.is_empty() {
             break;
         }
-        let (id, rule) = rule_parser(l).unwrap().1;
+        let (_, (id, rule)) = all_consuming(rule_parser)(l).map_err(|e| {
+            Error::nom(
+                DAY,
+                input,
+                e,
+                "a rule like `0: \"a\"`, `1: 2 3` or `1: 2 3 | 3 2`",
+            )
+        })?;
         rules.insert(id, rule);
+        sources.push(l);
+    }
+    // Every rule a rule refers to must be defined.
+    for l in sources {
+        let (head, body) = l.split_at(l.find(':').unwrap() + 1);
+        let mut offset = head.len();
+        for token in body.split(' ') {
+            if let Ok(id) = token.parse::<usize>() {
+                if !rules.contains_key(&id) {
+                    let at = &l[offset..offset + token.len()];
+                    return Err(Error::parse(DAY, input, at, "a defined rule number"));
+                }
+            }
+            offset += token.len() + 1;
+        }
     }
     let grammar = Grammar { rules };
-    let msgs = lines.collect();
-    (grammar, msgs)
+    let msgs = lines.map(String::from).collect();
+    Ok((grammar, msgs))
 }
-fn solve1(input: &str) -> usize {
-    let (grammar, msgs) = parse_grammar(input);
-    msgs.into_iter().filter(|l| grammar.accepts(l)).count()
+/// Number of messages that completely match rule 0.
+pub fn solve1(grammar: &Grammar, msgs: &[String]) -> usize {
+    msgs.iter().filter(|l| grammar.accepts(l)).count()
 }
-fn solve2(input: &str) -> usize {
-    let (mut grammar, msgs) = parse_grammar(input);
+/// Like [`solve1`], with rules 8 and 11 replaced by their looping versions.
+pub fn solve2(grammar: &Grammar, msgs: &[String]) -> usize {
+    let mut grammar = grammar.clone();
     grammar.rules.insert(8, Rule::Alt(vec![42], vec![42, 8]));
     grammar
         .rules
         .insert(11, Rule::Alt(vec![42, 31], vec![42, 11, 31]));
 
-    msgs.into_iter().filter(|l| grammar.accepts(l)).count()
+    msgs.iter().filter(|l| grammar.accepts(l)).count()
+}
+
+/// [`Solution`] for day 19.
+pub struct Day19;
+impl Solution for Day19 {
+    type Input = (Grammar, Vec<String>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_grammar(input)
+    }
+    fn part1((grammar, msgs): &Self::Input) -> Option<usize> {
+        Some(solve1(grammar, msgs))
+    }
+    fn part2((grammar, msgs): &Self::Input) -> Option<usize> {
+        Some(solve2(grammar, msgs))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::collections::HashMap;
 
-    use super::{rule_parser, solve1, solve2, Grammar, Rule};
+    use super::{parse_grammar, rule_parser, solve1, solve2, Grammar, Rule};
+    use crate::error::Error;
     #[test]
     fn parser_literal() {
         let (id, rule) = rule_parser(r#" 0: "a" "#.trim()).unwrap().1;
@@ -125,6 +183,26 @@ mod test {
         assert_eq!(id, 1);
         assert_eq!(rule, Rule::Alt(vec![2, 3], vec![5, 6, 7]));
     }
+    #[test]
+    fn parser_errors() {
+        assert_eq!(
+            parse_grammar("0: 1 | 1 1 | 1 1 1\n1: \"a\"\n\na")
+                .unwrap_err()
+                .to_string(),
+            "day 19, line 1, column 4: expected a rule like `0: \"a\"`, `1: 2 3` or \
+             `1: 2 3 | 3 2`, found `1`"
+        );
+        assert_eq!(
+            parse_grammar("0: 1 2\n1: \"a\"\n\na").unwrap_err(),
+            Error::Parse {
+                day: 19,
+                line: 1,
+                column: 6,
+                expected: "a defined rule number".to_owned(),
+                found: "`2`".to_owned(),
+            }
+        );
+    }
 
     #[test]
     fn small1() {
@@ -142,13 +220,8 @@ mod test {
             aaabbb
             aaaabbb
         "#;
-        assert_eq!(solve1(input), 2);
-    }
-
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day19.input").unwrap();
-        assert_eq!(solve1(&raw), 147);
+        let (grammar, msgs) = parse_grammar(input).unwrap();
+        assert_eq!(solve1(&grammar, &msgs), 2);
     }
 
     #[test]
@@ -213,13 +286,8 @@ mod test {
             babaaabbbaaabaababbaabababaaab
             aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba
         "#;
-        assert_eq!(solve1(input), 3);
-        assert_eq!(solve2(input), 12);
-    }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day19.input").unwrap();
-        assert_eq!(solve2(&raw), 263);
+        let (grammar, msgs) = parse_grammar(input).unwrap();
+        assert_eq!(solve1(&grammar, &msgs), 3);
+        assert_eq!(solve2(&grammar, &msgs), 12);
     }
 }
@@ -1,31 +1,57 @@
+//! Day 20: Jurassic Jigsaw.
+
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{collections::HashMap, str::FromStr};
 
-struct Tile {
-    id: usize,
-    rows: Vec<Vec<u8>>,
+use crate::{
+    error::Error,
+    grid::Grid,
+    solution::Solution,
+    transform::{Side, Transform},
+};
+
+const DAY: u8 = 20;
+
+/// A square camera image; each pixel is 1 (`#`) or 0 (`.`).
+pub struct Tile {
+    pub id: usize,
+    pub pixels: Grid<u8>,
 }
 impl Tile {
-    fn top(&self) -> Vec<u8> {
-        self.rows.first().unwrap().clone()
-    }
-    fn bottom(&self) -> Vec<u8> {
-        self.rows.last().unwrap().clone()
-    }
-    fn left(&self) -> Vec<u8> {
-        self.rows
-            .iter()
-            .map(|r| r.first().unwrap())
-            .copied()
-            .collect()
-    }
-    fn right(&self) -> Vec<u8> {
-        self.rows
-            .iter()
-            .map(|r| r.last().unwrap())
-            .copied()
-            .collect()
+    pub fn top(&self) -> Vec<u8> {
+        self.edge(Transform::IDENTITY, Side::Top).collect()
+    }
+    pub fn bottom(&self) -> Vec<u8> {
+        self.edge(Transform::IDENTITY, Side::Bottom).collect()
+    }
+    pub fn left(&self) -> Vec<u8> {
+        self.edge(Transform::IDENTITY, Side::Left).collect()
+    }
+    pub fn right(&self) -> Vec<u8> {
+        self.edge(Transform::IDENTITY, Side::Right).collect()
+    }
+
+    /// The pixel at `(row, col)` once the tile is transformed by `t`.
+    pub fn pixel(&self, t: Transform, pos: (usize, usize)) -> u8 {
+        let (r, c) = t.source(pos, self.dims());
+        self.pixels.items[r * self.pixels.width + c]
+    }
+    /// The pixels along `side` once the tile is transformed by `t`.
+    pub fn edge(&self, t: Transform, side: Side) -> impl Iterator<Item = u8> + '_ {
+        t.edge(side, self.dims())
+            .map(move |(r, c)| self.pixels.items[r * self.pixels.width + c])
+    }
+    /// A copy of the tile transformed by `t`.
+    pub fn oriented(&self, t: Transform) -> Tile {
+        Tile {
+            id: self.id,
+            pixels: self.pixels.transform(t),
+        }
+    }
+
+    fn dims(&self) -> (usize, usize) {
+        (self.pixels.height, self.pixels.width)
     }
 }
 
@@ -33,70 +59,227 @@ lazy_static! {
     static ref TILE_ID: Regex = Regex::new("^Tile (\\d+):$").unwrap();
 }
 impl FromStr for Tile {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.trim().lines().map(|l| l.trim());
-        let title = lines.next().ok_or(())?;
-        let id_raw = TILE_ID.captures(title).ok_or(())?.get(1).unwrap().as_str();
-        let id = id_raw.parse().map_err(|_| ())?;
-
-        let rows = lines
-            .map(|l| l.bytes().map(|b| if b == b'.' { 0 } else { 1 }).collect())
+        let lines: Vec<&str> = s
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
             .collect();
+        parse_tile(s, &lines)
+    }
+}
+
+/// Parses blank-line separated tiles, keyed by their distinct IDs.
+pub fn parse_tiles(input: &str) -> Result<HashMap<usize, Tile>, Error> {
+    let mut tiles = HashMap::new();
+    let mut lines = input.lines().map(|l| l.trim()).peekable();
+    while lines.peek().is_some() {
+        let block: Vec<&str> = lines.by_ref().take_while(|l| !l.is_empty()).collect();
+        if !block.is_empty() {
+            let tile = parse_tile(input, &block)?;
+            if tiles.contains_key(&tile.id) {
+                return Err(Error::parse(DAY, input, block[0], "a new tile ID"));
+            }
+            tiles.insert(tile.id, tile);
+        }
+    }
+    Ok(tiles)
+}
+
+// `lines` are non-empty subslices of `input`, which is only used to locate
+// errors.
+fn parse_tile(input: &str, lines: &[&str]) -> Result<Tile, Error> {
+    let (title, rows) = match lines.split_first() {
+        Some(split) => split,
+        None => {
+            return Err(Error::parse(
+                DAY,
+                input,
+                &input[input.len()..],
+                "`Tile <id>:`",
+            ))
+        }
+    };
+    let id = TILE_ID
+        .captures(title)
+        .and_then(|m| m[1].parse().ok())
+        .ok_or_else(|| Error::parse(DAY, input, title, "`Tile <id>:`"))?;
+    if rows.is_empty() {
+        let end = &title[title.len()..];
+        return Err(Error::parse(DAY, input, end, "a row of pixels"));
+    }
 
-        Ok(Tile { id, rows })
+    let pixels = Grid::parse_lines(DAY, input, rows, "`#` or `.`", |c| match c {
+        '#' => Some(1),
+        '.' => Some(0),
+        _ => None,
+    })?;
+    // Tiles are square.
+    if pixels.width != pixels.height {
+        let row = rows[0];
+        let at = &row[pixels.height.min(row.len())..];
+        let expected = format!("{} pixels per row", pixels.height);
+        return Err(Error::parse(DAY, input, at, expected));
+    }
+    Ok(Tile { id, pixels })
+}
+
+// Every tile edge, read in both directions, to the tiles that have it.
+fn edge_index(tiles: &HashMap<usize, Tile>) -> HashMap<Vec<u8>, Vec<usize>> {
+    let mut by_edge: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+    for (&id, tile) in tiles {
+        for mut edge in [tile.left(), tile.right(), tile.top(), tile.bottom()] {
+            by_edge.entry(edge.clone()).or_default().push(id);
+            edge.reverse();
+            by_edge.entry(edge).or_default().push(id);
+        }
     }
+    by_edge
 }
 
-fn solve1(input: &str) -> usize {
-    let tiles: HashMap<usize, Tile> = input
-        .trim()
-        .split("\n\n")
-        .map(|t| {
-            let tile: Tile = t.parse().unwrap();
-            (tile.id, tile)
+/// Product of the IDs of the four corner tiles; `None` unless there are
+/// exactly four.
+pub fn solve1(tiles: &HashMap<usize, Tile>) -> Option<usize> {
+    let corners = corners(tiles, &edge_index(tiles));
+    if corners.len() != 4 {
+        return None;
+    }
+    Some(corners.iter().product())
+}
+
+// Tiles with exactly two edges that match no other tile.
+fn corners(tiles: &HashMap<usize, Tile>, by_edge: &HashMap<Vec<u8>, Vec<usize>>) -> Vec<usize> {
+    let mut corners: Vec<usize> = tiles
+        .values()
+        .filter(|tile| {
+            let edges = [tile.left(), tile.right(), tile.top(), tile.bottom()];
+            edges.iter().filter(|e| by_edge[*e].len() == 1).count() == 2
         })
+        .map(|tile| tile.id)
         .collect();
-    let mut by_edge: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
-    for (&id, tile) in &tiles {
-        let mut left = tile.left();
-        let mut right = tile.right();
-        let mut top = tile.top();
-        let mut bottom = tile.bottom();
-        by_edge.entry(left.clone()).or_default().push(id);
-        by_edge.entry(right.clone()).or_default().push(id);
-        by_edge.entry(top.clone()).or_default().push(id);
-        by_edge.entry(bottom.clone()).or_default().push(id);
-
-        left.reverse();
-        right.reverse();
-        top.reverse();
-        bottom.reverse();
-
-        by_edge.entry(left).or_default().push(id);
-        by_edge.entry(right).or_default().push(id);
-        by_edge.entry(top).or_default().push(id);
-        by_edge.entry(bottom).or_default().push(id);
-    }
-
-    let mut corners = Vec::new();
-    for (&id, tile) in &tiles {
-        let left = by_edge[&tile.left()].len();
-        let right = by_edge[&tile.right()].len();
-        let top = by_edge[&tile.top()].len();
-        let bottom = by_edge[&tile.bottom()].len();
-        println!("{} -> ({}, {}, {}, {})", id, left, right, top, bottom);
-        if left + right + top + bottom == 6 {
-            corners.push(id);
+    corners.sort_unstable();
+    corners
+}
+
+/// Places every tile, oriented to match its neighbours, and joins them with
+/// their borders stripped. `None` if the tiles do not form a square image.
+pub fn assemble(tiles: &HashMap<usize, Tile>) -> Option<Grid<u8>> {
+    let n = (1..=tiles.len()).find(|n| n * n >= tiles.len())?;
+    if n * n != tiles.len() {
+        return None;
+    }
+    let by_edge = edge_index(tiles);
+    let unmatched = |edge: &Vec<u8>| by_edge[edge].len() == 1;
+    // The other tile with `edge`, oriented so that `side` of it reads `edge`.
+    let neighbor = |id: usize, edge: Vec<u8>, side: Side| {
+        let other = &tiles[by_edge[&edge].iter().find(|&&other| other != id)?];
+        let t = Transform::all().find(|&t| other.edge(t, side).eq(edge.iter().copied()))?;
+        Some((other, t))
+    };
+
+    // Start from a corner turned so that its unmatched edges face out.
+    let corner = &tiles[corners(tiles, &by_edge).first()?];
+    let first = Transform::all().find(|&t| {
+        unmatched(&corner.edge(t, Side::Top).collect())
+            && unmatched(&corner.edge(t, Side::Left).collect())
+    })?;
+
+    let mut placed: Vec<(&Tile, Transform)> = Vec::with_capacity(tiles.len());
+    placed.push((corner, first));
+    for idx in 1..tiles.len() {
+        let next = if idx % n == 0 {
+            let (above, t) = placed[idx - n];
+            neighbor(above.id, above.edge(t, Side::Bottom).collect(), Side::Top)?
+        } else {
+            let (left, t) = placed[idx - 1];
+            neighbor(left.id, left.edge(t, Side::Right).collect(), Side::Left)?
+        };
+        placed.push(next);
+    }
+
+    let inner = corner.pixels.height - 2;
+    let image = Grid::from_fn(n * inner, n * inner, |(i, j)| {
+        let (i, j) = (i as usize, j as usize);
+        let (tile, t) = placed[(i / inner) * n + j / inner];
+        tile.pixel(t, (i % inner + 1, j % inner + 1))
+    });
+    Some(image)
+}
+
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+/// Number of `#` pixels that are not part of a sea monster, in whichever
+/// orientation of the image has sea monsters.
+pub fn water_roughness(image: &Grid<u8>) -> Option<usize> {
+    let monster: Vec<(i32, i32)> = SEA_MONSTER
+        .iter()
+        .enumerate()
+        .flat_map(|(r, line)| {
+            line.char_indices()
+                .filter(|&(_, ch)| ch == '#')
+                .map(move |(c, _)| (r as i32, c as i32))
+        })
+        .collect();
+    let (mh, mw) = (SEA_MONSTER.len(), SEA_MONSTER[0].len());
+    for view in Transform::all().map(|t| image.transform(t)) {
+        let mut in_monster = view.map(|_| false);
+        let mut found = false;
+        for r in 0..(view.height + 1).saturating_sub(mh) as i32 {
+            for c in 0..(view.width + 1).saturating_sub(mw) as i32 {
+                let at = |&(dr, dc): &(i32, i32)| (r + dr, c + dc);
+                if monster.iter().all(|d| view.get(at(d)) == Some(&1)) {
+                    found = true;
+                    for d in &monster {
+                        in_monster.set(at(d), true);
+                    }
+                }
+            }
+        }
+        if found {
+            let rough = view
+                .items
+                .iter()
+                .zip(&in_monster.items)
+                .filter(|&(&px, &monster)| px == 1 && !monster)
+                .count();
+            return Some(rough);
         }
     }
-    corners.iter().copied().product()
+    None
+}
+
+/// [`Solution`] for day 20.
+pub struct Day20;
+impl Solution for Day20 {
+    type Input = HashMap<usize, Tile>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_tiles(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        solve1(input)
+    }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        water_roughness(&assemble(input)?)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{solve1, Tile};
+    use super::{assemble, parse_tiles, solve1, water_roughness, Tile};
+    use crate::{
+        error::Error,
+        grid::Grid,
+        transform::{Side, Transform},
+    };
 
     #[test]
     fn parser_test() {
@@ -115,7 +298,30 @@ mod test {
         ";
         let tile = raw.parse::<Tile>().unwrap();
         assert_eq!(tile.id, 2311);
-        assert_eq!(tile.rows.len(), 10);
+        assert_eq!((tile.pixels.width, tile.pixels.height), (10, 10));
+        assert_eq!(
+            parse_tiles("Tile 1:\n#.\n..\n\nTile 2:\n#.\n.")
+                .err()
+                .unwrap(),
+            Error::Parse {
+                day: 20,
+                line: 7,
+                column: 2,
+                expected: "a row of width 2".to_owned(),
+                found: "end of input".to_owned(),
+            }
+        );
+        assert_eq!(
+            parse_tiles("Tile x:\n#").err().unwrap().to_string(),
+            "day 20, line 1, column 1: expected `Tile <id>:`, found `Tile`"
+        );
+        assert_eq!(
+            parse_tiles("Tile 1:\n#\n\nTile 1:\n.")
+                .err()
+                .unwrap()
+                .to_string(),
+            "day 20, line 4, column 1: expected a new tile ID, found `Tile`"
+        );
     }
 
     const SMALL: &str = r"
@@ -230,12 +436,34 @@ mod test {
 
     #[test]
     fn small1() {
-        assert_eq!(solve1(SMALL), 20899048083289);
+        assert_eq!(solve1(&parse_tiles(SMALL).unwrap()), Some(20899048083289));
+        assert_eq!(solve1(&parse_tiles("").unwrap()), None);
+    }
+
+    #[test]
+    fn transforms() {
+        let tile = Tile {
+            id: 1,
+            pixels: Grid::new(3, 3, vec![1, 0, 0, 1, 1, 0, 0, 0, 0]),
+        };
+        for t in Transform::all() {
+            let copy = tile.oriented(t);
+            assert_eq!(tile.edge(t, Side::Top).collect::<Vec<_>>(), copy.top());
+            assert_eq!(tile.edge(t, Side::Right).collect::<Vec<_>>(), copy.right());
+            assert_eq!(tile.pixel(t, (1, 2)), copy.pixels.items[5]);
+        }
+        let turned = tile.oriented(Transform::ROTATE);
+        assert_eq!(turned.pixels.items, vec![0, 1, 1, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(
+            turned.oriented(Transform::ROTATE.inverse()).pixels,
+            tile.pixels
+        );
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day20.input").unwrap();
-        assert_eq!(solve1(&raw), 18449208814679);
+    fn small2() {
+        let image = assemble(&parse_tiles(SMALL).unwrap()).unwrap();
+        assert_eq!((image.width, image.height), (24, 24));
+        assert_eq!(water_roughness(&image), Some(273));
     }
 }
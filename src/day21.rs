@@ -1,20 +1,38 @@
+//! Day 21: Allergen Assessment.
+
 use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 
 use nom::{
-    bytes::{complete::tag, streaming::take_while1},
-    character::complete::{multispace1, space1},
+    bytes::complete::{tag, take_while1},
+    character::complete::{multispace0, multispace1, space1},
+    combinator::all_consuming,
     multi::separated_list1,
     sequence::delimited,
     IResult,
 };
 
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 21;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Food {
-    ingredients: HashSet<String>,
-    allergens: HashSet<String>,
+/// One line of the food list.
+pub struct Food {
+    pub ingredients: HashSet<String>,
+    pub allergens: HashSet<String>,
 }
 
-fn input_parser(input: &str) -> IResult<&str, Vec<Food>> {
+/// Parses one food per line.
+pub fn parse(input: &str) -> Result<Vec<Food>, Error> {
+    all_consuming(delimited(multispace0, input_parser, multispace0))(input)
+        .map(|(_, foods)| foods)
+        .map_err(|e| {
+            let expected = "a food like `abc def (contains pqr, tuv)`";
+            Error::nom(DAY, input, e, expected)
+        })
+}
+/// nom parser for a list of foods, one per line.
+pub fn input_parser(input: &str) -> IResult<&str, Vec<Food>> {
     separated_list1(multispace1, food_parser)(input)
 }
 fn food_parser(input: &str) -> IResult<&str, Food> {
@@ -38,9 +56,11 @@ fn token_parser(input: &str) -> IResult<&str, String> {
     Ok((input, token.to_owned()))
 }
 
-fn solve(foods: &[Food]) -> (usize, String) {
-    type Ingredient = String;
-    type Allergen = String;
+type Ingredient = String;
+type Allergen = String;
+
+// The ingredients each allergen could be in: those in every food listing it.
+fn candidates(foods: &[Food]) -> HashMap<Allergen, HashSet<Ingredient>> {
     let mut candidates: HashMap<Allergen, HashSet<Ingredient>> = HashMap::new();
     for food in foods {
         for allergen in &food.allergens {
@@ -55,26 +75,27 @@ fn solve(foods: &[Food]) -> (usize, String) {
             };
         }
     }
-    let all: HashSet<Ingredient> = foods
-        .iter()
-        .flat_map(|food| food.ingredients.iter())
-        .cloned()
-        .collect();
-    let suspicious: HashSet<Ingredient> = candidates
-        .values()
-        .flat_map(|ps| ps.iter())
-        .cloned()
-        .collect();
-    let innocent: HashSet<Ingredient> = all.difference(&suspicious).cloned().collect();
-    let occurrences = foods
+    candidates
+}
+
+/// Occurrences of ingredients that cannot contain an allergen.
+pub fn solve1(foods: &[Food]) -> usize {
+    let suspicious: HashSet<Ingredient> = candidates(foods).into_values().flatten().collect();
+    foods
         .iter()
         .flat_map(|food| food.ingredients.iter())
-        .filter(|&ingredient| innocent.contains(ingredient))
-        .count();
+        .filter(|&ingredient| !suspicious.contains(ingredient))
+        .count()
+}
+
+/// The dangerous ingredients sorted by their allergen; `None` if the foods do
+/// not pin down which ingredient contains each allergen.
+pub fn solve2(foods: &[Food]) -> Option<String> {
+    let mut candidates = candidates(foods);
     let mut dangerous: BTreeMap<Allergen, Ingredient> = BTreeMap::new();
     while dangerous.len() < candidates.len() {
         let (ingredient, allergen) = {
-            let (ingredient, allergens) = candidates.iter().find(|(_, ps)| ps.len() == 1).unwrap();
+            let (ingredient, allergens) = candidates.iter().find(|(_, ps)| ps.len() == 1)?;
             (ingredient.clone(), allergens.iter().next().unwrap().clone())
         };
         dangerous.insert(ingredient.clone(), allergen.clone());
@@ -83,12 +104,31 @@ fn solve(foods: &[Food]) -> (usize, String) {
         }
     }
     let dangerous: Vec<Ingredient> = dangerous.values().cloned().collect();
-    (occurrences, dangerous.join(","))
+    Some(dangerous.join(","))
+}
+
+/// [`Solution`] for day 21.
+pub struct Day21;
+impl Solution for Day21 {
+    type Input = Vec<Food>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(solve1(input))
+    }
+    fn part2(input: &Self::Input) -> Option<String> {
+        solve2(input)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{food_parser, input_parser, solve, token_parser, Food};
+    use super::{food_parser, input_parser, parse, solve1, solve2, token_parser, Food};
+    use crate::error::Error;
 
     #[test]
     fn parser_test() {
@@ -96,8 +136,18 @@ mod test {
         assert_eq!(
             food_parser("abc def (contains pqr, tuv)").unwrap().1,
             Food {
-                ingredients: vec!["abc", "def"].iter().map(|&s| s.to_owned()).collect(),
-                allergens: vec!["pqr", "tuv"].iter().map(|&s| s.to_owned()).collect(),
+                ingredients: ["abc", "def"].iter().map(|&s| s.to_owned()).collect(),
+                allergens: ["pqr", "tuv"].iter().map(|&s| s.to_owned()).collect(),
+            }
+        );
+        assert_eq!(
+            parse("abc (contains pqr)\ndef (contains tuv").unwrap_err(),
+            Error::Parse {
+                day: 21,
+                line: 2,
+                column: 1,
+                expected: "a food like `abc def (contains pqr, tuv)`".to_owned(),
+                found: "`def`".to_owned(),
             }
         );
     }
@@ -111,16 +161,16 @@ mod test {
     #[test]
     fn small1() {
         let parsed = input_parser(SMALL.trim()).unwrap().1;
-        let (p1, p2) = solve(&parsed);
-        assert_eq!(p1, 5);
-        assert_eq!(p2, "mxmxvkd,sqjhc,fvjkl");
+        assert_eq!(solve1(&parsed), 5);
+        assert_eq!(solve2(&parsed), Some("mxmxvkd,sqjhc,fvjkl".to_owned()));
     }
+
     #[test]
-    fn normal() {
-        let raw = std::fs::read_to_string("data/day21.input").unwrap();
-        let parsed = input_parser(raw.trim()).unwrap().1;
-        let (p1, p2) = solve(&parsed);
-        assert_eq!(p1, 2410);
-        assert_eq!(p2, "tmp,pdpgm,cdslv,zrvtg,ttkn,mkpmkx,vxzpfp,flnhl");
+    fn ambiguous() {
+        // Either ingredient could hold either allergen, but `ghi` holds
+        // neither.
+        let foods = parse("abc def (contains pqr, tuv)\nabc def ghi (contains pqr)").unwrap();
+        assert_eq!(solve1(&foods), 1);
+        assert_eq!(solve2(&foods), None);
     }
 }
@@ -1,16 +1,31 @@
+//! Day 22: Crab Combat.
+
 use std::collections::{hash_map::DefaultHasher, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
 
 use nom::{
     bytes::complete::{tag, take_while},
-    character::complete::multispace1,
-    combinator::{map, map_res},
+    character::complete::{multispace0, multispace1},
+    combinator::{all_consuming, map, map_res},
     multi::separated_list1,
+    sequence::delimited,
     IResult,
 };
 
-type Player = VecDeque<usize>;
-fn input_parser(input: &str) -> IResult<&str, (Vec<usize>, Vec<usize>)> {
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 22;
+
+/// A deck, top card first.
+pub type Player = VecDeque<usize>;
+/// Parses both starting decks.
+pub fn parse(input: &str) -> Result<(Vec<usize>, Vec<usize>), Error> {
+    all_consuming(delimited(multispace0, input_parser, multispace0))(input)
+        .map(|(_, players)| players)
+        .map_err(|e| Error::nom(DAY, input, e, "`Player 1:` and `Player 2:` decks of cards"))
+}
+/// nom parser for both starting decks.
+pub fn input_parser(input: &str) -> IResult<&str, (Vec<usize>, Vec<usize>)> {
     let (input, _) = tag("Player 1:")(input)?;
     let (input, _) = multispace1(input)?;
     let (input, p1) = player_parser(input)?;
@@ -26,12 +41,15 @@ fn player_parser(input: &str) -> IResult<&str, Vec<usize>> {
     })(input)
 }
 fn usize_parser(input: &str) -> IResult<&str, usize> {
-    map_res(take_while(|c: char| c.is_digit(10)), |s: &str| s.parse())(input)
+    map_res(take_while(|c: char| c.is_ascii_digit()), |s: &str| {
+        s.parse()
+    })(input)
 }
 
-fn solve1(p1: &[usize], p2: &[usize]) -> usize {
-    let mut p1: VecDeque<usize> = p1.iter().copied().collect();
-    let mut p2: VecDeque<usize> = p2.iter().copied().collect();
+/// Winning score of a game of Combat.
+pub fn solve1(p1: &[usize], p2: &[usize]) -> usize {
+    let mut p1: Player = p1.iter().copied().collect();
+    let mut p2: Player = p2.iter().copied().collect();
 
     while !p1.is_empty() && !p2.is_empty() {
         let c1 = p1.pop_front().unwrap();
@@ -51,10 +69,11 @@ fn solve1(p1: &[usize], p2: &[usize]) -> usize {
     }
 }
 
-fn solve2(p1: &[usize], p2: &[usize]) -> usize {
-    let mut p1: VecDeque<usize> = p1.iter().copied().collect();
-    let mut p2: VecDeque<usize> = p2.iter().copied().collect();
-    let winner = play_game(&mut p1, &mut p2, 0);
+/// Winning score of a game of Recursive Combat.
+pub fn solve2(p1: &[usize], p2: &[usize]) -> usize {
+    let mut p1: Player = p1.iter().copied().collect();
+    let mut p2: Player = p2.iter().copied().collect();
+    let winner = play_game(&mut p1, &mut p2);
     match winner {
         Winner::P1 => score(&p1),
         Winner::P2 => score(&p2),
@@ -62,17 +81,18 @@ fn solve2(p1: &[usize], p2: &[usize]) -> usize {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Winner {
+pub enum Winner {
     P1,
     P2,
 }
 
-fn snapshot(p1: &VecDeque<usize>, p2: &VecDeque<usize>) -> u64 {
+fn snapshot(p1: &Player, p2: &Player) -> u64 {
     let mut h = DefaultHasher::new();
     (p1, p2).hash(&mut h);
     h.finish()
 }
-fn play_game(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>, depth: usize) -> Winner {
+/// Plays a game of Recursive Combat in place, returning its winner.
+pub fn play_game(p1: &mut Player, p2: &mut Player) -> Winner {
     let mut seen: HashSet<u64> = HashSet::new();
     loop {
         if p1.is_empty() {
@@ -81,16 +101,16 @@ fn play_game(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>, depth: usize) -
         if p2.is_empty() {
             return Winner::P1;
         }
-        if !seen.insert(snapshot(&p1, &p2)) {
+        if !seen.insert(snapshot(p1, p2)) {
             return Winner::P1;
         }
         let c1 = p1.pop_front().unwrap();
         let c2 = p2.pop_front().unwrap();
 
         let winner = if p1.len() >= c1 && p2.len() >= c2 {
-            let mut p1: VecDeque<usize> = p1.iter().copied().take(c1).collect();
-            let mut p2: VecDeque<usize> = p2.iter().copied().take(c2).collect();
-            play_game(&mut p1, &mut p2, depth + 1)
+            let mut p1: Player = p1.iter().copied().take(c1).collect();
+            let mut p2: Player = p2.iter().copied().take(c2).collect();
+            play_game(&mut p1, &mut p2)
         } else if c1 > c2 {
             Winner::P1
         } else {
@@ -109,7 +129,7 @@ fn play_game(p1: &mut VecDeque<usize>, p2: &mut VecDeque<usize>, depth: usize) -
     }
 }
 
-fn score(deck: &VecDeque<usize>) -> usize {
+fn score(deck: &Player) -> usize {
     deck.iter()
         .rev()
         .enumerate()
@@ -117,9 +137,27 @@ fn score(deck: &VecDeque<usize>) -> usize {
         .sum()
 }
 
+/// [`Solution`] for day 22.
+pub struct Day22;
+impl Solution for Day22 {
+    type Input = (Vec<usize>, Vec<usize>);
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1((p1, p2): &Self::Input) -> Option<usize> {
+        Some(solve1(p1, p2))
+    }
+    fn part2((p1, p2): &Self::Input) -> Option<usize> {
+        Some(solve2(p1, p2))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{input_parser, player_parser, solve1, solve2};
+    use super::{input_parser, parse, player_parser, solve1, solve2};
     #[test]
     fn parser_test() {
         let p = player_parser("3 1 4").unwrap().1;
@@ -127,6 +165,13 @@ mod test {
         let (p1, p2) = input_parser(SMALL.trim()).unwrap().1;
         assert_eq!(p1, vec![9, 2, 6, 3, 1]);
         assert_eq!(p2, vec![5, 8, 4, 7, 10]);
+        assert_eq!(
+            parse("Player 1:\n9 2\n\nPlayer 3:\n5 8")
+                .unwrap_err()
+                .to_string(),
+            "day 22, line 4, column 1: expected `Player 1:` and `Player 2:` decks of cards, \
+             found `Player`"
+        );
     }
 
     const SMALL: &str = r"
@@ -149,23 +194,9 @@ mod test {
         let (p1, p2) = input_parser(SMALL.trim()).unwrap().1;
         assert_eq!(solve1(&p1, &p2), 306);
     }
-    #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day22.input").unwrap();
-        let (p1, p2) = input_parser(raw.trim()).unwrap().1;
-        assert_eq!(solve1(&p1, &p2), 31754);
-    }
-
     #[test]
     fn small2() {
         let (p1, p2) = input_parser(SMALL.trim()).unwrap().1;
         assert_eq!(solve2(&p1, &p2), 291);
     }
-
-    #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day22.input").unwrap();
-        let (p1, p2) = input_parser(raw.trim()).unwrap().1;
-        assert_eq!(solve2(&p1, &p2), 35436);
-    }
 }
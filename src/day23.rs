@@ -1,9 +1,17 @@
-struct Ring {
+//! Day 23: Crab Cups.
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 23;
+
+/// The circle of cups, stored as a successor table indexed by label.
+pub struct Ring {
     next: Vec<usize>,
     cur: usize,
 }
 impl Ring {
-    fn to_vec(&self, init: usize) -> Vec<usize> {
+    /// The labels clockwise, starting from `init`.
+    pub fn to_vec(&self, init: usize) -> Vec<usize> {
         let mut xs = vec![init];
         let mut cur = self.next[init];
         while cur != init {
@@ -12,7 +20,8 @@ impl Ring {
         }
         xs
     }
-    fn step(&mut self) {
+    /// Performs a single move of the crab.
+    pub fn step(&mut self) {
         let snip0 = self.next[self.cur];
         let snip1 = self.next[snip0];
         let snip2 = self.next[snip1];
@@ -51,9 +60,95 @@ impl From<&[usize]> for Ring {
     }
 }
 
+/// Parses the cup labels, one digit per cup; the labels must be a
+/// permutation of `1..=n` for some `n >= 5`.
+pub fn parse(input: &str) -> Result<Vec<usize>, Error> {
+    let labels = input.trim();
+    let n = labels.chars().count();
+    let mut seen = vec![false; n + 1];
+    let mut cups = Vec::with_capacity(n);
+    for (i, c) in labels.char_indices() {
+        match c.to_digit(10).map(|d| d as usize) {
+            Some(d) if (1..=n).contains(&d) && !seen[d] => {
+                seen[d] = true;
+                cups.push(d);
+            }
+            _ => {
+                let expected = format!("an unused label from 1 to {}", n);
+                return Err(Error::parse(DAY, input, &labels[i..], expected));
+            }
+        }
+    }
+    if n < 5 {
+        let end = &labels[labels.len()..];
+        return Err(Error::parse(DAY, input, end, "at least five cups"));
+    }
+    Ok(cups)
+}
+
+/// Labels after cup 1 following 100 moves.
+pub fn solve1(cups: &[usize]) -> String {
+    let mut ring = Ring::from(cups);
+    for _ in 0..100 {
+        ring.step();
+    }
+    ring.to_vec(1)[1..].iter().map(|c| c.to_string()).collect()
+}
+
+/// Product of the two cups after cup 1, with a million cups and ten million
+/// moves.
+pub fn solve2(cups: &[usize]) -> u64 {
+    let mut xs = cups.to_vec();
+    for i in cups.len() + 1..=1_000_000 {
+        xs.push(i);
+    }
+    let mut ring = Ring::from(xs.as_ref());
+    for _ in 0..10_000_000 {
+        ring.step();
+    }
+
+    let n1 = ring.next[1];
+    let n2 = ring.next[n1];
+    n1 as u64 * n2 as u64
+}
+
+/// [`Solution`] for day 23.
+pub struct Day23;
+impl Solution for Day23 {
+    type Input = Vec<usize>;
+    type Part1 = String;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(input: &Self::Input) -> Option<String> {
+        Some(solve1(input))
+    }
+    fn part2(input: &Self::Input) -> Option<u64> {
+        Some(solve2(input))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::Ring;
+    use super::{parse, solve1, solve2, Ring};
+
+    #[test]
+    fn parser() {
+        assert_eq!(
+            parse("389125467\n").unwrap(),
+            vec![3, 8, 9, 1, 2, 5, 4, 6, 7]
+        );
+        assert_eq!(
+            parse("389125437").unwrap_err().to_string(),
+            "day 23, line 1, column 8: expected an unused label from 1 to 9, found `37`"
+        );
+        assert_eq!(
+            parse("3124").unwrap_err().to_string(),
+            "day 23, line 1, column 5: expected at least five cups, found end of input"
+        );
+    }
 
     #[test]
     fn ring_test() {
@@ -68,30 +163,11 @@ mod test {
             ring.step();
         }
         assert_eq!(ring.to_vec(1), vec![1, 6, 7, 3, 8, 4, 5, 2, 9]);
+        assert_eq!(solve1(&parse("389125467").unwrap()), "67384529");
     }
 
     #[test]
-    fn normal1() {
-        let mut ring: Ring = Ring::from([4, 9, 6, 1, 3, 8, 5, 2, 7].as_ref());
-        for _ in 0..100 {
-            ring.step();
-        }
-        assert_eq!(ring.to_vec(1), vec![1, 6, 9, 4, 2, 5, 8, 3, 7]);
-    }
-
-    #[test]
-    fn normal2() {
-        let mut xs = vec![4, 9, 6, 1, 3, 8, 5, 2, 7];
-        for i in 10..=1_000_000 {
-            xs.push(i);
-        }
-        let mut ring = Ring::from(xs.as_ref());
-        for _ in 0..10_000_000 {
-            ring.step();
-        }
-
-        let n1 = ring.next[1];
-        let n2 = ring.next[n1];
-        assert_eq!(n1 as i64 * n2 as i64, 218882971435);
+    fn small2() {
+        assert_eq!(solve2(&parse("389125467").unwrap()), 149245887792);
     }
 }
@@ -1,124 +1,243 @@
-use std::collections::{HashMap, HashSet};
-
-use nom::{branch::alt, bytes::complete::tag, combinator::value, multi::many1, IResult};
-
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
-    E,
-    SE,
-    SW,
-    W,
-    NW,
-    NE,
+//! Day 24: Lobby Layout.
+
+use std::{fmt, fmt::Write};
+
+use nom::combinator::all_consuming;
+
+use crate::{
+    automaton::{Automaton, LifeRule, Unbounded},
+    error::Error,
+    grid::{Grid, InfiniteGrid, Pos},
+    hex::{offset_bounds, path_parser, Coordinate, Path},
+    recorder::{Draw, Layer, Recorder},
+    solution::Solution,
+};
+
+const DAY: u8 = 24;
+
+/// Parses one path per line, e.g. `esenee`.
+pub fn parse_paths(input: &str) -> Result<Vec<Path>, Error> {
+    input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            all_consuming(path_parser)(line)
+                .map(|(_, path)| path)
+                .map_err(|e| Error::nom(DAY, input, e, "`e`, `se`, `sw`, `w`, `nw` or `ne`"))
+        })
+        .collect()
 }
-type Path = Vec<Direction>;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Coordinate(i32, i32);
+/// The lobby floor: which tiles are black.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct Floor {
+    /// Black tiles are set to `true`, keyed by their axial coordinates;
+    /// white ones are unset.
+    pub black: InfiniteGrid<bool>,
+}
 
-fn shift(Coordinate(p, q): Coordinate, dir: Direction) -> Coordinate {
-    match dir {
-        Direction::E => Coordinate(p + 1, q),
-        Direction::SE => Coordinate(p, q + 1),
-        Direction::SW => Coordinate(p - 1, q + 1),
-        Direction::W => Coordinate(p - 1, q),
-        Direction::NW => Coordinate(p, q - 1),
-        Direction::NE => Coordinate(p + 1, q - 1),
+impl Floor {
+    /// The floor after following every path from the reference tile.
+    pub fn from_paths(paths: &[Path]) -> Floor {
+        let mut black = InfiniteGrid::new();
+        for path in paths {
+            let tile = Coordinate::ORIGIN.traverse(path).into();
+            if black.remove(tile).is_none() {
+                black.set(tile, true);
+            }
+        }
+        Floor { black }
+    }
+    pub fn count_black(&self) -> usize {
+        self.black.len()
+    }
+    /// The floor after `days` days of flipping under `rule`.
+    pub fn evolve(&self, rule: LifeRule, days: usize) -> Floor {
+        let black = automaton(rule).run(self.black.clone(), days);
+        Floor { black }
+    }
+    /// The offset-coordinate box around the black tiles; `None` if there are
+    /// none.
+    pub fn bounds(&self) -> Option<((i32, i32), (i32, i32))> {
+        let tiles: Vec<Coordinate> = self.black.positions().map(Coordinate::from).collect();
+        offset_bounds(&tiles)
+    }
+    pub fn is_black(&self, tile: Coordinate) -> bool {
+        self.black.contains(tile.into())
+    }
+
+    /// One line per row of tiles in the bounding box, `#` for black and `.`
+    /// for white, with odd rows indented half a tile.
+    fn lines(&self) -> Vec<String> {
+        let ((top, left), (bottom, right)) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return Vec::new(),
+        };
+        (top..=bottom)
+            .map(|row| {
+                let tiles: Vec<&str> = (left..=right)
+                    .map(|col| {
+                        if self.is_black(Coordinate::from_offset((row, col))) {
+                            "#"
+                        } else {
+                            "."
+                        }
+                    })
+                    .collect();
+                let indent = if row & 1 == 1 { " " } else { "" };
+                format!("{}{}", indent, tiles.join(" "))
+            })
+            .collect()
+    }
+
+    /// The bounding box as an SVG image of pointy-topped hexagons.
+    pub fn svg(&self) -> String {
+        const SIZE: f64 = 10.0;
+        let width = SIZE * 3f64.sqrt();
+        let center = |(row, col): (i32, i32)| {
+            let x = width * (col as f64 + 0.5 * (row & 1) as f64);
+            (x, 1.5 * SIZE * row as f64)
+        };
+
+        let mut svg = String::new();
+        let ((top, left), (bottom, right)) = self.bounds().unwrap_or(((0, 0), (-1, -1)));
+        let (min_x, min_y) = (width * (left as f64 - 0.5), 1.5 * SIZE * top as f64 - SIZE);
+        let (max_x, max_y) = (
+            width * (right as f64 + 1.0),
+            1.5 * SIZE * bottom as f64 + SIZE,
+        );
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.1} {:.1} {:.1} {:.1}">"#,
+            min_x,
+            min_y,
+            (max_x - min_x).max(0.0),
+            (max_y - min_y).max(0.0)
+        )
+        .unwrap();
+        for row in top..=bottom {
+            for col in left..=right {
+                let (x, y) = center((row, col));
+                let corners: Vec<String> = (0..6)
+                    .map(|k| {
+                        let angle = (60.0 * k as f64 - 30.0).to_radians();
+                        format!(
+                            "{:.1},{:.1}",
+                            x + SIZE * angle.cos(),
+                            y + SIZE * angle.sin()
+                        )
+                    })
+                    .collect();
+                let black = self.is_black(Coordinate::from_offset((row, col)));
+                let fill = if black { "#202020" } else { "#f0f0f0" };
+                writeln!(
+                    svg,
+                    r##"  <polygon points="{}" fill="{}" stroke="#909090"/>"##,
+                    corners.join(" "),
+                    fill
+                )
+                .unwrap();
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+impl fmt::Display for Floor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+impl Draw for Floor {
+    /// The text of [`Floor`]'s `Display`, one character per cell.
+    fn draw(&self) -> Vec<Layer> {
+        let lines: Vec<Vec<char>> = self.lines().iter().map(|l| l.chars().collect()).collect();
+        let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let grid = Grid::from_fn(width, lines.len(), |(i, j)| {
+            lines[i as usize].get(j as usize).copied().unwrap_or(' ')
+        });
+        vec![Layer {
+            label: String::new(),
+            grid,
+        }]
     }
 }
-fn traverse(origin: Coordinate, path: &Path) -> Coordinate {
-    path.into_iter().fold(origin, |p, &dir| shift(p, dir))
+
+/// Flips tiles under `rule`, with black tiles live.
+fn automaton(rule: LifeRule) -> Automaton<Unbounded<bool, impl Fn(Pos) -> Vec<Pos>>, LifeRule> {
+    let neighbors = |tile: Pos| {
+        let tiles = Coordinate::from(tile).neighbors();
+        tiles.iter().map(|&n| n.into()).collect()
+    };
+    Automaton::new(Unbounded::new(neighbors, |&black| black), rule)
 }
-fn neighbors(Coordinate(p, q): Coordinate) -> Vec<Coordinate> {
-    vec![
-        Coordinate(p + 1, q),
-        Coordinate(p, q + 1),
-        Coordinate(p - 1, q + 1),
-        Coordinate(p - 1, q),
-        Coordinate(p, q - 1),
-        Coordinate(p + 1, q - 1),
-    ]
+
+/// Number of black tiles after following every path.
+pub fn solve1(paths: &[Path]) -> usize {
+    Floor::from_paths(paths).count_black()
 }
 
-fn path_parser(input: &str) -> IResult<&str, Path> {
-    many1(direction_parser)(input)
+/// The rule the lobby floor follows: a white tile with exactly 2 black
+/// neighbors turns black, and a black tile with 1 or 2 stays black.
+pub const RULE: &str = "B2/S12";
+
+/// Number of black tiles after a further 100 days of flipping.
+pub fn solve2(paths: &[Path]) -> usize {
+    simulate(paths, RULE.parse().unwrap(), 100)
 }
-fn direction_parser(input: &str) -> IResult<&str, Direction> {
-    alt((
-        value(Direction::E, tag("e")),
-        value(Direction::SE, tag("se")),
-        value(Direction::SW, tag("sw")),
-        value(Direction::W, tag("w")),
-        value(Direction::NW, tag("nw")),
-        value(Direction::NE, tag("ne")),
-    ))(input)
+
+/// Number of black tiles after following every path and then `days` days of
+/// flipping under `rule`.
+pub fn simulate(paths: &[Path], rule: LifeRule, days: usize) -> usize {
+    Floor::from_paths(paths).evolve(rule, days).count_black()
 }
 
-fn solve1(paths: &[Path]) -> usize {
-    let mut flipped = HashSet::new();
-    for path in paths {
-        let tile = traverse(Coordinate(0, 0), path);
-        if flipped.contains(&tile) {
-            flipped.remove(&tile);
-        } else {
-            flipped.insert(tile);
-        }
+/// Records the floor after following every path and after each of `days`
+/// days of flipping under `rule`.
+pub fn record(paths: &[Path], rule: LifeRule, days: usize, recorder: &mut Recorder) {
+    for black in automaton(rule)
+        .generations(Floor::from_paths(paths).black)
+        .take(days + 1)
+    {
+        recorder.record(&Floor { black });
     }
-    flipped.len()
 }
 
-fn solve2(paths: &[Path]) -> usize {
-    let mut flipped = HashSet::new();
-    for path in paths {
-        let tile = traverse(Coordinate(0, 0), path);
-        if flipped.contains(&tile) {
-            flipped.remove(&tile);
-        } else {
-            flipped.insert(tile);
-        }
-    }
-    for _ in 0..100 {
-        flipped = evolve(flipped);
+/// [`Solution`] for day 24.
+pub struct Day24;
+impl Solution for Day24 {
+    type Input = Vec<Path>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse_paths(input)
     }
-    flipped.len()
-}
-fn evolve(black_tiles: HashSet<Coordinate>) -> HashSet<Coordinate> {
-    let mut black_neighbors: HashMap<Coordinate, usize> = HashMap::new();
-    for &tile in &black_tiles {
-        for n in neighbors(tile) {
-            *black_neighbors.entry(n).or_default() += 1;
-        }
+    fn part1(input: &Self::Input) -> Option<usize> {
+        Some(solve1(input))
     }
-    let mut next = HashSet::new();
-    for (tile, neighbor_count) in black_neighbors {
-        let is_black = black_tiles.contains(&tile);
-        let stay_black = is_black && neighbor_count == 1 || neighbor_count == 2;
-        let flip_white = !is_black && neighbor_count == 2;
-        if stay_black || flip_white {
-            next.insert(tile);
-        }
+    fn part2(input: &Self::Input) -> Option<usize> {
+        Some(solve2(input))
     }
-    next
 }
 
 #[cfg(test)]
 mod test {
-    use super::{path_parser, solve1, solve2, Direction, Path};
+    use super::{parse_paths, record, simulate, solve1, solve2, Floor, Path, RULE};
+    use crate::{hex::path_parser, recorder::Recorder};
 
     #[test]
     fn parser_test() {
+        assert_eq!(parse_paths("ese\nnww").unwrap().len(), 2);
         assert_eq!(
-            path_parser("ese").unwrap().1,
-            vec![Direction::E, Direction::SE]
+            parse_paths("ese\nnwnx").unwrap_err().to_string(),
+            "day 24, line 2, column 3: expected `e`, `se`, `sw`, `w`, `nw` or `ne`, found `nx`"
         );
-    }
-
-    fn parse_paths(input: &str) -> Vec<Path> {
-        input
-            .trim()
-            .lines()
-            .map(|line| path_parser(line.trim()).unwrap().1)
-            .collect()
+        assert!(parse_paths("esx").is_err());
     }
 
     const SMALL: &str = r"
@@ -146,27 +265,54 @@ mod test {
 
     #[test]
     fn small1() {
-        let paths: Vec<Path> = parse_paths(SMALL);
+        let paths: Vec<Path> = parse_paths(SMALL).unwrap();
         assert_eq!(solve1(&paths), 10);
     }
 
     #[test]
-    fn normal1() {
-        let raw = std::fs::read_to_string("data/day24.input").unwrap();
-        let paths: Vec<Path> = parse_paths(&raw);
-        assert_eq!(solve1(&paths), 488);
+    fn small2() {
+        let paths: Vec<Path> = parse_paths(SMALL).unwrap();
+        assert_eq!(solve2(&paths), 2208);
     }
 
     #[test]
-    fn small2() {
-        let paths: Vec<Path> = parse_paths(SMALL);
-        assert_eq!(solve2(&paths), 2208);
+    fn rules() {
+        let paths: Vec<Path> = parse_paths(SMALL).unwrap();
+        assert_eq!(simulate(&paths, "B2/S12".parse().unwrap(), 10), 37);
+        // Every black tile survives and nothing is born.
+        assert_eq!(simulate(&paths, "B/S0123456".parse().unwrap(), 10), 10);
+    }
+
+    #[test]
+    fn floor() {
+        let paths: Vec<Path> = ["e", "se", "nwwswee"]
+            .iter()
+            .map(|p| path_parser(p).unwrap().1)
+            .collect();
+        let floor = Floor::from_paths(&paths);
+        assert_eq!(floor.bounds(), Some(((0, 0), (1, 1))));
+        assert_eq!(floor.to_string(), "# #\n # .\n");
+        assert_eq!(Floor::default().to_string(), "");
+
+        let svg = floor.svg();
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-8.7 -10.0 43.3 35.0">"#
+        ));
+        assert_eq!(svg.matches("<polygon").count(), 4);
+        assert_eq!(svg.matches(r##"fill="#202020""##).count(), 3);
     }
 
     #[test]
-    fn normal2() {
-        let raw = std::fs::read_to_string("data/day24.input").unwrap();
-        let paths: Vec<Path> = parse_paths(&raw);
-        assert_eq!(solve2(&paths), 4118);
+    fn recording() {
+        let paths: Vec<Path> = parse_paths(SMALL).unwrap();
+        let mut recorder = Recorder::new();
+        record(&paths, RULE.parse().unwrap(), 10, &mut recorder);
+        assert_eq!(recorder.frames.len(), 11);
+        // Day 1 of the puzzle's example has 15 black tiles, day 10 has 37.
+        let black = |day: usize| {
+            let grid = &recorder.frames[day].layers[0].grid;
+            grid.items.iter().filter(|&&c| c == '#').count()
+        };
+        assert_eq!((black(0), black(1), black(10)), (10, 15, 37));
     }
 }
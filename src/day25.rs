@@ -1,3 +1,9 @@
+//! Day 25: Combo Breaker.
+
+use crate::{error::Error, solution::Solution};
+
+const DAY: u8 = 25;
+
 const MAGIC: u64 = 20201227;
 struct Transform {
     subject: u64,
@@ -18,17 +24,75 @@ impl Iterator for Transform {
     }
 }
 
-fn solve1(card: u64, door: u64) -> u64 {
+/// Parses the card and door public keys.
+pub fn parse(input: &str) -> Result<(u64, u64), Error> {
+    let mut tokens = input.split_ascii_whitespace();
+    let end = &input[input.len()..];
+    let mut key = || {
+        let token = tokens
+            .next()
+            .ok_or_else(|| Error::parse(DAY, input, end, "a public key"))?;
+        token
+            .parse()
+            .ok()
+            .filter(|k| (1..MAGIC).contains(k))
+            .ok_or_else(|| {
+                let expected = format!("a public key from 1 to {}", MAGIC - 1);
+                Error::parse(DAY, input, token, expected)
+            })
+    };
+    let keys = (key()?, key()?);
+    match tokens.next() {
+        Some(extra) => Err(Error::parse(DAY, input, extra, "end of input")),
+        None => Ok(keys),
+    }
+}
+
+/// The encryption key the card and door agree on.
+pub fn solve1(card: u64, door: u64) -> u64 {
     let card_loop = Transform::new(7)
         .enumerate()
         .find(|&(_, v)| v == card)
         .unwrap()
         .0;
-    Transform::new(door).nth(card_loop as usize).unwrap()
+    Transform::new(door).nth(card_loop).unwrap()
+}
+/// [`Solution`] for day 25.
+pub struct Day25;
+impl Solution for Day25 {
+    type Input = (u64, u64);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, Error> {
+        parse(input)
+    }
+    fn part1(&(card, door): &Self::Input) -> Option<u64> {
+        Some(solve1(card, door))
+    }
+    fn part2(_input: &Self::Input) -> Option<u64> {
+        // There is no second puzzle on the last day.
+        None
+    }
 }
+
 #[cfg(test)]
 mod test {
-    use super::solve1;
+    use super::{parse, solve1};
+
+    #[test]
+    fn parser() {
+        assert_eq!(parse("5764801\n17807724\n").unwrap(), (5764801, 17807724));
+        assert_eq!(
+            parse("5764801\n20201227").unwrap_err().to_string(),
+            "day 25, line 2, column 1: expected a public key from 1 to 20201226, \
+             found `20201227`"
+        );
+        assert_eq!(
+            parse("5764801").unwrap_err().to_string(),
+            "day 25, line 1, column 8: expected a public key, found end of input"
+        );
+    }
 
     #[test]
     fn small1() {
@@ -36,11 +100,4 @@ mod test {
         let door = 17807724;
         assert_eq!(solve1(card, door), 14897079);
     }
-
-    #[test]
-    fn normal1() {
-        let card = 12090988;
-        let door = 240583;
-        assert_eq!(solve1(card, door), 3015200);
-    }
 }
@@ -0,0 +1,138 @@
+use std::{fmt, path::PathBuf};
+
+/// Everything that can go wrong turning puzzle input into answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The input for `day` is malformed at `line`, `column` (both 1-based).
+    Parse {
+        day: u8,
+        line: usize,
+        column: usize,
+        expected: String,
+        found: String,
+    },
+    /// There is no solver for the requested day.
+    UnknownDay(u32),
+    /// The requested day has nothing to record.
+    NoRecording(u32),
+    /// A file could not be read.
+    Io { path: PathBuf, message: String },
+    /// A file could not be written.
+    Write { path: PathBuf, message: String },
+    /// An answer manifest is malformed.
+    Manifest { path: PathBuf, message: String },
+}
+
+impl Error {
+    /// A parse error at the start of `at`, which must be a subslice of `input`
+    /// (typically the unconsumed remainder or an offending token).
+    pub fn parse(day: u8, input: &str, at: &str, expected: impl Into<String>) -> Error {
+        let offset = offset(input, at);
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let rest = &input[offset..];
+        let token: String = rest.chars().take_while(|c| !c.is_whitespace()).collect();
+        let found = if rest.is_empty() {
+            "end of input".to_owned()
+        } else if rest.starts_with('\n') {
+            "end of line".to_owned()
+        } else if token.is_empty() {
+            "whitespace".to_owned()
+        } else {
+            format!("`{}`", token)
+        };
+        Error::Parse {
+            day,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: expected.into(),
+            found,
+        }
+    }
+
+    /// A parse error at the position where a nom parser over (a subslice of)
+    /// `input` gave up, skipping any whitespace it stopped in front of.
+    pub fn nom(
+        day: u8,
+        input: &str,
+        err: nom::Err<nom::error::Error<&str>>,
+        expected: impl Into<String>,
+    ) -> Error {
+        let at = match err {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.input.trim_start(),
+            nom::Err::Incomplete(_) => &input[input.len()..],
+        };
+        Error::parse(day, input, at, expected)
+    }
+}
+
+// Byte offset of `at` within `input`, or 0 if it is not a subslice.
+fn offset(input: &str, at: &str) -> usize {
+    let start = input.as_ptr() as usize;
+    let pos = at.as_ptr() as usize;
+    if start <= pos && pos <= start + input.len() {
+        pos - start
+    } else {
+        0
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse {
+                day,
+                line,
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "day {}, line {}, column {}: expected {}, found {}",
+                day, line, column, expected, found
+            ),
+            Error::UnknownDay(day) => write!(f, "no solver for day {}", day),
+            Error::NoRecording(day) => write!(f, "nothing to record for day {}", day),
+            Error::Io { path, message } => {
+                write!(f, "could not read {}: {}", path.display(), message)
+            }
+            Error::Write { path, message } => {
+                write!(f, "could not write {}: {}", path.display(), message)
+            }
+            Error::Manifest { path, message } => {
+                write!(f, "invalid manifest {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::Error;
+
+    #[test]
+    fn position() {
+        let input = "nop +0\nacc +1\njmp x4\n";
+        let at = &input[18..];
+        assert_eq!(
+            Error::parse(8, input, at, "a number"),
+            Error::Parse {
+                day: 8,
+                line: 3,
+                column: 5,
+                expected: "a number".to_owned(),
+                found: "`x4`".to_owned(),
+            }
+        );
+        assert_eq!(
+            Error::parse(8, input, &input[input.len()..], "`nop`").to_string(),
+            "day 8, line 4, column 1: expected `nop`, found end of input"
+        );
+        assert_eq!(
+            Error::parse(8, input, &input[6..], "`+`").to_string(),
+            "day 8, line 1, column 7: expected `+`, found end of line"
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, iter::FromIterator};
 
+use crate::{error::Error, transform::Transform};
+
+/// A dense, row-major 2-D grid.
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct Grid<T> {
     pub width: usize,
@@ -7,22 +10,39 @@ pub struct Grid<T> {
     pub items: Vec<T>,
 }
 
+/// A `(row, column)` position; signed so that neighbors of edge cells can be
+/// expressed (and rejected by [`Grid::get`]).
 pub type Pos = (i32, i32);
 
+/// The 4 orthogonal steps, clockwise from up.
+pub const DIRECTIONS4: [Pos; 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+/// The 8 orthogonal and diagonal steps, in row-major order.
+pub const DIRECTIONS8: [Pos; 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
 impl<T: fmt::Display> fmt::Display for Grid<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for i in 0..self.height {
             for j in 0..self.width {
                 write!(f, "{}", self.get((i as i32, j as i32)).unwrap())?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
 impl<T> Grid<T> {
-    pub fn new<'a>(width: usize, height: usize, items: Vec<T>) -> Grid<T> {
+    /// Wraps `items`, which must hold exactly `width * height` cells.
+    pub fn new(width: usize, height: usize, items: Vec<T>) -> Grid<T> {
         assert_eq!(width * height, items.len());
         Grid {
             width,
@@ -30,6 +50,7 @@ impl<T> Grid<T> {
             items,
         }
     }
+    /// Builds a grid by calling `f` on every position.
     pub fn from_fn<F>(width: usize, height: usize, f: F) -> Grid<T>
     where
         F: Fn(Pos) -> T,
@@ -47,11 +68,396 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn get(&self, (i, j): Pos) -> Option<&T> {
-        if 0 <= i && i < self.height as i32 && 0 <= j && j < self.width as i32 {
-            Some(&self.items[i as usize * self.width + j as usize])
+    /// The cell at `pos`, or `None` if it lies outside the grid.
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if self.contains(pos) {
+            Some(&self.items[pos.0 as usize * self.width + pos.1 as usize])
+        } else {
+            None
+        }
+    }
+
+    /// Parses one row per non-blank line of `input`, mapping each character
+    /// with `cell`. Errors name `expected` for characters `cell` rejects.
+    pub fn parse<F>(day: u8, input: &str, expected: &str, cell: F) -> Result<Grid<T>, Error>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        Grid::parse_lines(day, input, &lines, expected, cell)
+    }
+    /// Like [`Grid::parse`], for `lines` that are subslices of `input`.
+    pub fn parse_lines<F>(
+        day: u8,
+        input: &str,
+        lines: &[&str],
+        expected: &str,
+        cell: F,
+    ) -> Result<Grid<T>, Error>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let width = lines
+            .first()
+            .ok_or_else(|| Error::parse(day, input, &input[input.len()..], "a grid row"))?
+            .chars()
+            .count();
+        let mut items = Vec::with_capacity(width * lines.len());
+        for line in lines {
+            let mut count = 0;
+            for (i, c) in line.char_indices() {
+                items.push(cell(c).ok_or_else(|| Error::parse(day, input, &line[i..], expected))?);
+                count += 1;
+            }
+            if count != width {
+                // Point at the first extra character, or the end of a short
+                // row.
+                let end = line
+                    .char_indices()
+                    .nth(width)
+                    .map_or(line.len(), |(i, _)| i);
+                let at = &line[end..];
+                let expected = format!("a row of width {}", width);
+                return Err(Error::parse(day, input, at, expected));
+            }
+        }
+        Ok(Grid::new(width, lines.len(), items))
+    }
+
+    /// Whether `pos` lies inside the grid.
+    pub fn contains(&self, (i, j): Pos) -> bool {
+        0 <= i && i < self.height as i32 && 0 <= j && j < self.width as i32
+    }
+    /// The cell at `pos`, or `None` if it lies outside the grid.
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.contains(pos) {
+            Some(&mut self.items[pos.0 as usize * self.width + pos.1 as usize])
         } else {
             None
         }
     }
+    /// Replaces the cell at `pos`, returning the old one; `None` (and no
+    /// change) if `pos` lies outside the grid.
+    pub fn set(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// `pos` moved inside the grid by wrapping around its edges, as if the
+    /// grid were a torus. Panics if the grid is empty.
+    pub fn wrap(&self, (i, j): Pos) -> Pos {
+        (
+            i.rem_euclid(self.height as i32),
+            j.rem_euclid(self.width as i32),
+        )
+    }
+    /// The cell at `pos` on the torus; `None` only if the grid is empty.
+    pub fn get_wrapped(&self, pos: Pos) -> Option<&T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        self.get(self.wrap(pos))
+    }
+    /// The cell at `pos` on the torus; `None` only if the grid is empty.
+    pub fn get_wrapped_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        self.get_mut(self.wrap(pos))
+    }
+
+    /// Every position, in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let (width, height) = (self.width as i32, self.height as i32);
+        (0..height).flat_map(move |i| (0..width).map(move |j| (i, j)))
+    }
+    /// Every cell with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.items.iter())
+    }
+    /// Every row, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.items.chunks(self.width.max(1))
+    }
+    /// The cells of column `j`, top to bottom; empty if there is no such
+    /// column.
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        (0..self.height).filter_map(move |i| self.get((i as i32, j as i32)))
+    }
+
+    /// The orthogonal neighbors of `pos` that lie inside the grid.
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.steps(pos, &DIRECTIONS4)
+    }
+    /// The orthogonal and diagonal neighbors of `pos` that lie inside the
+    /// grid.
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.steps(pos, &DIRECTIONS8)
+    }
+    fn steps<'a>(&'a self, (i, j): Pos, dirs: &'a [Pos]) -> impl Iterator<Item = Pos> + 'a {
+        dirs.iter()
+            .map(move |&(di, dj)| (i + di, j + dj))
+            .filter(move |&p| self.contains(p))
+    }
+    /// The cells seen looking from `pos` (exclusive) in direction
+    /// `(di, dj)`, up to the edge of the grid.
+    pub fn ray(&self, (i, j): Pos, (di, dj): Pos) -> impl Iterator<Item = (Pos, &T)> {
+        (1..)
+            .map(move |k| (i + k * di, j + k * dj))
+            .map(move |p| self.get(p).map(|cell| (p, cell)))
+            .take_while(Option::is_some)
+            .flatten()
+    }
+
+    /// Applies `f` to every cell.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            items: self.items.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// A copy of the grid transformed by `t`.
+    pub fn transform(&self, t: Transform) -> Grid<T> {
+        let dims = (self.height, self.width);
+        let (height, width) = t.dims(dims);
+        Grid::from_fn(width, height, |(i, j)| {
+            let (r, c) = t.source((i as usize, j as usize), dims);
+            self.items[r * self.width + c].clone()
+        })
+    }
+}
+
+/// A sparse grid over the whole plane that grows as cells are set. Cells
+/// that were never set are absent.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct InfiniteGrid<T> {
+    cells: HashMap<Pos, T>,
+}
+
+impl<T> Default for InfiniteGrid<T> {
+    fn default() -> Self {
+        InfiniteGrid {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for InfiniteGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        InfiniteGrid {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> From<Grid<T>> for InfiniteGrid<T> {
+    fn from(grid: Grid<T>) -> Self {
+        grid.positions().zip(grid.items).collect()
+    }
+}
+
+impl<T> InfiniteGrid<T> {
+    pub fn new() -> InfiniteGrid<T> {
+        InfiniteGrid::default()
+    }
+    /// Number of cells that are set.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Whether the cell at `pos` is set.
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.cells.contains_key(&pos)
+    }
+    /// The cell at `pos`, or `None` if it was never set.
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+    /// The cell at `pos`, or `None` if it was never set.
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+    /// The cell at `pos`, set to the default first if it was never set.
+    pub fn entry(&mut self, pos: Pos) -> &mut T
+    where
+        T: Default,
+    {
+        self.cells.entry(pos).or_default()
+    }
+    /// Sets the cell at `pos`, returning the old one if there was one.
+    pub fn set(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.cells.insert(pos, value)
+    }
+    /// Unsets the cell at `pos`, returning it if it was set.
+    pub fn remove(&mut self, pos: Pos) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    /// Every set position, in no particular order.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.cells.keys().copied()
+    }
+    /// Every set cell with its position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells.iter().map(|(&pos, cell)| (pos, cell))
+    }
+    /// The top-left and bottom-right corners of the smallest rectangle
+    /// holding every set cell, or `None` if no cell is set.
+    pub fn bounds(&self) -> Option<(Pos, Pos)> {
+        let mut positions = self.positions();
+        let first = positions.next()?;
+        Some(positions.fold((first, first), |(lo, hi), (i, j)| {
+            ((lo.0.min(i), lo.1.min(j)), (hi.0.max(i), hi.1.max(j)))
+        }))
+    }
+
+    /// The orthogonal neighbors of `pos`.
+    pub fn neighbors4(&self, (i, j): Pos) -> impl Iterator<Item = Pos> {
+        DIRECTIONS4.iter().map(move |&(di, dj)| (i + di, j + dj))
+    }
+    /// The orthogonal and diagonal neighbors of `pos`.
+    pub fn neighbors8(&self, (i, j): Pos) -> impl Iterator<Item = Pos> {
+        DIRECTIONS8.iter().map(move |&(di, dj)| (i + di, j + dj))
+    }
+
+    /// Applies `f` to every set cell.
+    pub fn map<U, F>(&self, mut f: F) -> InfiniteGrid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        self.iter().map(|(pos, cell)| (pos, f(cell))).collect()
+    }
+}
+
+impl<T: Clone> InfiniteGrid<T> {
+    /// A dense copy of [`InfiniteGrid::bounds`], with unset cells filled by
+    /// `fill`, and the position of its top-left corner.
+    pub fn to_grid(&self, fill: T) -> (Pos, Grid<T>) {
+        let ((top, left), (bottom, right)) = self.bounds().unwrap_or(((0, 0), (-1, -1)));
+        let (width, height) = ((right - left + 1) as usize, (bottom - top + 1) as usize);
+        let grid = Grid::from_fn(width, height, |(i, j)| {
+            self.get((top + i, left + j)).unwrap_or(&fill).clone()
+        });
+        ((top, left), grid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Grid, InfiniteGrid};
+    use crate::transform::Transform;
+
+    fn grid() -> Grid<u8> {
+        Grid::parse(0, "123\n456", "a digit", |c| {
+            c.to_digit(10).map(|d| d as u8)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parser() {
+        assert_eq!(grid(), Grid::new(3, 2, vec![1, 2, 3, 4, 5, 6]));
+        let digit = |c: char| c.to_digit(10);
+        assert_eq!(
+            Grid::parse(3, "12\n1x", "a digit", digit)
+                .unwrap_err()
+                .to_string(),
+            "day 3, line 2, column 2: expected a digit, found `x`"
+        );
+        assert_eq!(
+            Grid::parse(3, "12\n123", "a digit", digit)
+                .unwrap_err()
+                .to_string(),
+            "day 3, line 2, column 3: expected a row of width 2, found `3`"
+        );
+        assert!(Grid::parse(3, "\n", "a digit", digit).is_err());
+
+        // Widths count characters, not bytes.
+        let grid = Grid::parse(3, "é.\n.ü", "anything", Some).unwrap();
+        assert_eq!(grid, Grid::new(2, 2, vec!['é', '.', '.', 'ü']));
+        assert_eq!(
+            Grid::parse(3, "é.\nüüü", "anything", Some)
+                .unwrap_err()
+                .to_string(),
+            "day 3, line 2, column 3: expected a row of width 2, found `ü`"
+        );
+    }
+
+    #[test]
+    fn access() {
+        let mut grid = grid();
+        assert_eq!(grid.set((1, 2), 9), Some(6));
+        assert_eq!(grid.set((2, 0), 9), None);
+        *grid.get_mut((0, 0)).unwrap() += 10;
+        assert_eq!(grid.items, vec![11, 2, 3, 4, 5, 9]);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![&[11, 2, 3], &[4, 5, 9]]
+        );
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), vec![&2, &5]);
+        assert_eq!(grid.column(3).count(), 0);
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+        assert_eq!(
+            grid.map(|&x| x % 2 == 0).items,
+            vec![false, true, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = grid();
+        assert_eq!(
+            grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+        let ray: Vec<_> = grid.ray((1, 0), (0, 1)).map(|(_, &x)| x).collect();
+        assert_eq!(ray, vec![5, 6]);
+        assert_eq!(grid.ray((0, 0), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn transform() {
+        let turned = grid().transform(Transform::ROTATE);
+        assert_eq!(turned, Grid::new(2, 3, vec![4, 1, 5, 2, 6, 3]));
+    }
+
+    #[test]
+    fn wrapping() {
+        let grid = grid();
+        assert_eq!(grid.wrap((-1, 7)), (1, 1));
+        assert_eq!(grid.get_wrapped((2, -1)), Some(&3));
+        assert_eq!(Grid::<u8>::new(0, 0, vec![]).get_wrapped((1, 1)), None);
+    }
+
+    #[test]
+    fn infinite() {
+        let mut grid: InfiniteGrid<u8> = grid().into();
+        assert_eq!(grid.get((1, 2)), Some(&6));
+        assert_eq!(grid.set((-2, 4), 7), None);
+        *grid.entry((5, 0)) += 1;
+        assert_eq!(grid.remove((0, 0)), Some(1));
+        assert_eq!(grid.len(), 7);
+        assert_eq!(grid.bounds(), Some(((-2, 0), (5, 4))));
+        assert_eq!(grid.neighbors8((-100, 0)).count(), 8);
+        let (origin, dense) = grid.map(|&x| x * 2).to_grid(0);
+        assert_eq!(origin, (-2, 0));
+        assert_eq!((dense.width, dense.height), (5, 8));
+        assert_eq!(dense.get((0, 4)), Some(&14));
+        assert_eq!(dense.get((2, 0)), Some(&0));
+        assert_eq!(InfiniteGrid::<u8>::new().to_grid(0).1.items, vec![]);
+    }
 }
@@ -0,0 +1,352 @@
+//! Geometry on a grid of pointy-topped hexagons, whose neighbors lie east,
+//! west and along the four diagonals.
+//!
+//! Tiles use axial [`Coordinate`]s: the first grows to the east, the second
+//! to the south-east.
+
+use std::{
+    fmt,
+    ops::{Add, Sub},
+    str::FromStr,
+};
+
+use nom::{branch::alt, bytes::complete::tag, combinator::value, multi::many1, IResult};
+
+use crate::grid::Pos;
+
+/// A step to one of the six neighbors of a hexagonal tile.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Direction {
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+    NE,
+}
+/// A walk from some tile.
+pub type Path = Vec<Direction>;
+
+impl Direction {
+    /// Every direction, clockwise from east.
+    pub const ALL: [Direction; 6] = [
+        Direction::E,
+        Direction::SE,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+        Direction::NE,
+    ];
+
+    /// The direction `turns` sixths of a turn clockwise from `self`.
+    pub fn rotate(self, turns: i32) -> Direction {
+        let index = Direction::ALL.iter().position(|&d| d == self).unwrap() as i32;
+        Direction::ALL[(index + turns).rem_euclid(6) as usize]
+    }
+    /// The step to the neighbor in this direction.
+    pub fn offset(self) -> Coordinate {
+        match self {
+            Direction::E => Coordinate(1, 0),
+            Direction::SE => Coordinate(0, 1),
+            Direction::SW => Coordinate(-1, 1),
+            Direction::W => Coordinate(-1, 0),
+            Direction::NW => Coordinate(0, -1),
+            Direction::NE => Coordinate(1, -1),
+        }
+    }
+}
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Direction::E => "e",
+            Direction::SE => "se",
+            Direction::SW => "sw",
+            Direction::W => "w",
+            Direction::NW => "nw",
+            Direction::NE => "ne",
+        })
+    }
+}
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match direction_parser(s) {
+            Ok(("", dir)) => Ok(dir),
+            _ => Err(format!(
+                "invalid direction: {} (expected e, se, sw, w, nw or ne)",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses one direction, e.g. `se`.
+pub fn direction_parser(input: &str) -> IResult<&str, Direction> {
+    alt((
+        value(Direction::E, tag("e")),
+        value(Direction::SE, tag("se")),
+        value(Direction::SW, tag("sw")),
+        value(Direction::W, tag("w")),
+        value(Direction::NW, tag("nw")),
+        value(Direction::NE, tag("ne")),
+    ))(input)
+}
+/// Parses a non-empty run of directions with nothing between them, e.g.
+/// `esenee`.
+pub fn path_parser(input: &str) -> IResult<&str, Path> {
+    many1(direction_parser)(input)
+}
+/// The inverse of [`path_parser`].
+pub fn format_path(path: &[Direction]) -> String {
+    path.iter().map(Direction::to_string).collect()
+}
+
+/// Axial coordinates of a hexagonal tile.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub struct Coordinate(pub i32, pub i32);
+
+/// Cube coordinates `(x, y, z)` of a tile, with `x + y + z == 0`.
+pub type Cube = (i32, i32, i32);
+
+/// Axial coordinates as a [`Pos`], for storing tiles in an
+/// [`InfiniteGrid`](crate::grid::InfiniteGrid).
+impl From<Coordinate> for Pos {
+    fn from(Coordinate(q, r): Coordinate) -> Pos {
+        (q, r)
+    }
+}
+impl From<Pos> for Coordinate {
+    fn from((q, r): Pos) -> Coordinate {
+        Coordinate(q, r)
+    }
+}
+
+impl Add for Coordinate {
+    type Output = Coordinate;
+    fn add(self, Coordinate(p, q): Coordinate) -> Coordinate {
+        Coordinate(self.0 + p, self.1 + q)
+    }
+}
+impl Sub for Coordinate {
+    type Output = Coordinate;
+    fn sub(self, Coordinate(p, q): Coordinate) -> Coordinate {
+        Coordinate(self.0 - p, self.1 - q)
+    }
+}
+
+impl Coordinate {
+    pub const ORIGIN: Coordinate = Coordinate(0, 0);
+
+    /// The neighbor in direction `dir`.
+    pub fn shift(self, dir: Direction) -> Coordinate {
+        self + dir.offset()
+    }
+    /// Where following `path` from `self` ends up.
+    pub fn traverse(self, path: &[Direction]) -> Coordinate {
+        path.iter().fold(self, |p, &dir| p.shift(dir))
+    }
+    /// All six neighbors, clockwise from east.
+    pub fn neighbors(self) -> [Coordinate; 6] {
+        Direction::ALL.map(|dir| self.shift(dir))
+    }
+
+    /// The number of steps between `self` and `other`.
+    pub fn distance(self, other: Coordinate) -> u32 {
+        let (x, y, z) = (self - other).to_cube();
+        (x.unsigned_abs() + y.unsigned_abs() + z.unsigned_abs()) / 2
+    }
+    /// Every tile exactly `radius` steps away, clockwise from the north-west
+    /// corner.
+    pub fn ring(self, radius: u32) -> Vec<Coordinate> {
+        if radius == 0 {
+            return vec![self];
+        }
+        let radius = radius as i32;
+        let mut tile = self + Coordinate(0, -radius);
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        for &dir in &Direction::ALL {
+            for _ in 0..radius {
+                ring.push(tile);
+                tile = tile.shift(dir);
+            }
+        }
+        ring
+    }
+    /// Every tile at most `radius` steps away: `self`, then each
+    /// [`Coordinate::ring`] outwards.
+    pub fn spiral(self, radius: u32) -> Vec<Coordinate> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+    /// The tiles along the straight line from `self` to `other`, both
+    /// included.
+    pub fn line_to(self, other: Coordinate) -> Vec<Coordinate> {
+        let n = self.distance(other);
+        if n == 0 {
+            return vec![self];
+        }
+        let (a, b) = (self.to_cube(), other.to_cube());
+        // Nudge the line off tile edges, so that rounding is consistent.
+        let lerp =
+            |a: i32, b: i32, t: f64, nudge: f64| a as f64 + 1e-6 * nudge + (b - a) as f64 * t;
+        (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                Coordinate::round((
+                    lerp(a.0, b.0, t, 1.0),
+                    lerp(a.1, b.1, t, 2.0),
+                    lerp(a.2, b.2, t, -3.0),
+                ))
+            })
+            .collect()
+    }
+    // The tile containing fractional cube coordinates.
+    fn round((x, y, z): (f64, f64, f64)) -> Coordinate {
+        let (mut rx, ry, mut rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if dx > dy && dx > dz {
+            rx = -ry - rz;
+        } else if dy <= dz {
+            rz = -rx - ry;
+        }
+        Coordinate(rx as i32, rz as i32)
+    }
+    /// `self` turned `turns` sixths of a turn clockwise about `center`.
+    pub fn rotate(self, center: Coordinate, turns: i32) -> Coordinate {
+        let (mut x, mut y, mut z) = (self - center).to_cube();
+        for _ in 0..turns.rem_euclid(6) {
+            (x, y, z) = (-z, -x, -y);
+        }
+        center + Coordinate::from_cube((x, y, z))
+    }
+
+    pub fn to_cube(self) -> Cube {
+        let Coordinate(q, r) = self;
+        (q, -q - r, r)
+    }
+    pub fn from_cube((x, _, z): Cube) -> Coordinate {
+        Coordinate(x, z)
+    }
+    /// The `(row, column)` of the tile when drawn in rows, with odd rows
+    /// shifted half a tile to the east.
+    pub fn to_offset(self) -> (i32, i32) {
+        let Coordinate(q, r) = self;
+        (r, q + (r - (r & 1)) / 2)
+    }
+    /// The inverse of [`Coordinate::to_offset`].
+    pub fn from_offset((row, col): (i32, i32)) -> Coordinate {
+        Coordinate(col - (row - (row & 1)) / 2, row)
+    }
+}
+
+/// The smallest `(top-left, bottom-right)` box of offset coordinates (see
+/// [`Coordinate::to_offset`]) holding every tile; `None` if there are none.
+pub fn offset_bounds<'a, I>(tiles: I) -> Option<((i32, i32), (i32, i32))>
+where
+    I: IntoIterator<Item = &'a Coordinate>,
+{
+    let mut offsets = tiles.into_iter().map(|c| c.to_offset());
+    let first = offsets.next()?;
+    Some(offsets.fold((first, first), |(lo, hi), (row, col)| {
+        (
+            (lo.0.min(row), lo.1.min(col)),
+            (hi.0.max(row), hi.1.max(col)),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_path, offset_bounds, path_parser, Coordinate, Direction};
+
+    #[test]
+    fn parser() {
+        assert_eq!(
+            path_parser("ese").unwrap().1,
+            vec![Direction::E, Direction::SE]
+        );
+        assert_eq!(format_path(&path_parser("nwwswee").unwrap().1), "nwwswee");
+        assert_eq!("sw".parse(), Ok(Direction::SW));
+        assert!("s".parse::<Direction>().is_err());
+        assert!("see".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn steps() {
+        let origin = Coordinate::ORIGIN;
+        let path = path_parser("nwwswee").unwrap().1;
+        assert_eq!(origin.traverse(&path), origin);
+        for (dir, n) in Direction::ALL.iter().zip(origin.neighbors().iter()) {
+            assert_eq!(origin.shift(*dir), *n);
+            assert_eq!(n.distance(origin), 1);
+            assert_eq!(dir.rotate(3).offset() + dir.offset(), origin);
+        }
+        assert_eq!(Direction::E.rotate(-1), Direction::NE);
+        assert_eq!(Coordinate(3, -1).distance(Coordinate(-1, 2)), 4);
+    }
+
+    #[test]
+    fn rings() {
+        let center = Coordinate(2, -3);
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..4 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|&c| c.distance(center) == radius));
+            // Consecutive tiles touch, all the way round.
+            for (i, &c) in ring.iter().enumerate() {
+                assert_eq!(c.distance(ring[(i + 1) % ring.len()]), 1);
+            }
+        }
+        let mut spiral = center.spiral(3);
+        assert_eq!(spiral.len(), 37);
+        spiral.sort();
+        spiral.dedup();
+        assert_eq!(spiral.len(), 37);
+    }
+
+    #[test]
+    fn lines() {
+        let (a, b) = (Coordinate(-2, 1), Coordinate(3, -1));
+        let line = a.line_to(b);
+        assert_eq!(line.len(), a.distance(b) as usize + 1);
+        assert_eq!((line[0], line[line.len() - 1]), (a, b));
+        for pair in line.windows(2) {
+            assert_eq!(pair[0].distance(pair[1]), 1);
+        }
+        assert_eq!(a.line_to(a), vec![a]);
+    }
+
+    #[test]
+    fn rotation() {
+        let center = Coordinate(1, 1);
+        let tile = center.shift(Direction::E).shift(Direction::E);
+        assert_eq!(
+            tile.rotate(center, 1),
+            center.shift(Direction::SE).shift(Direction::SE)
+        );
+        assert_eq!(tile.rotate(center, 6), tile);
+        assert_eq!(tile.rotate(center, -2), tile.rotate(center, 4));
+        assert_eq!(tile.rotate(center, 3).distance(tile), 4);
+    }
+
+    #[test]
+    fn conversions() {
+        for c in Coordinate(0, 0).spiral(3) {
+            let (x, y, z) = c.to_cube();
+            assert_eq!(x + y + z, 0);
+            assert_eq!(Coordinate::from_cube((x, y, z)), c);
+            assert_eq!(Coordinate::from_offset(c.to_offset()), c);
+        }
+        // Odd rows sit half a tile east, so south-east of (0, 0) is (1, 0).
+        assert_eq!(Coordinate::ORIGIN.shift(Direction::SE).to_offset(), (1, 0));
+        assert_eq!(Coordinate::ORIGIN.shift(Direction::SW).to_offset(), (1, -1));
+        assert_eq!(Coordinate(1, 1).shift(Direction::SE).to_offset(), (2, 2));
+
+        assert_eq!(offset_bounds(&[]), None);
+        assert_eq!(
+            offset_bounds(&Coordinate(1, 0).ring(1)),
+            Some(((-1, 0), (1, 2)))
+        );
+    }
+}
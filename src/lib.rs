@@ -1,29 +1,47 @@
-#![allow(dead_code)]
+//! Solutions to Advent of Code 2020.
+//!
+//! Each `dayNN` module exposes that day's parser, solvers and input types, and
+//! implements [`Solution`] so that days can be run generically (see
+//! [`runner`]).
 
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
 
-mod grid;
+pub mod answers;
+pub mod automaton;
+pub mod error;
+pub mod grid;
+pub mod hex;
+
+pub mod recorder;
+pub mod runner;
+pub mod solution;
+pub mod transform;
+
+pub use error::Error;
+pub use grid::{Grid, InfiniteGrid, Pos};
+pub use solution::Solution;
+pub use transform::{Side, Transform};
@@ -1,3 +1,168 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::Path,
+    process,
+    time::Duration,
+};
+
+use advent_of_code_2020::{
+    answers,
+    day08::{self, Analysis, Debugger, Interpreter},
+    recorder::Format,
+    runner::{self, Part},
+};
+
+const USAGE: &str = "usage: advent-of-code-2020 <day> [1|2|both] [input-path|-]
+       advent-of-code-2020 check [data-dir]
+       advent-of-code-2020 record <11|17|24> [play|txt|ppm|png] [input-path|-] [out-dir]
+       advent-of-code-2020 debug <program-path>
+       advent-of-code-2020 analyze <program-path> [dot]";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("check") => check(&args[1..]),
+        Some("record") => record(&args[1..]),
+        Some("debug") => debug(&args[1..]),
+        Some("analyze") => analyze(&args[1..]),
+        _ => run(&args),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let day = parse_day(args.first())?;
+    let part: Part = match args.get(1) {
+        Some(part) => part.parse()?,
+        None => Part::Both,
+    };
+    let input = read_input(args.get(2))?;
+
+    let report = runner::run(day, part, &input).map_err(|err| err.to_string())?;
+    println!("day {:02} parse: ({:?})", day, report.parse_elapsed);
+    for answer in report.answers {
+        let value = answer.value.as_deref().unwrap_or("no answer");
+        println!(
+            "day {:02} part {}: {} ({:?})",
+            day, answer.part, value, answer.elapsed
+        );
+    }
+    Ok(())
+}
+
+fn parse_day(arg: Option<&String>) -> Result<u32, String> {
+    match arg {
+        Some(day) => day.parse().map_err(|_| format!("invalid day: {}", day)),
+        None => Err("missing day".to_owned()),
+    }
+}
+
+// Reads the file at `arg`, or stdin if it is missing or `-`.
+fn read_input(arg: Option<&String>) -> Result<String, String> {
+    match arg.map(String::as_str) {
+        None | Some("-") => {
+            let mut buf = String::new();
+            io::stdin()
+                .read_to_string(&mut buf)
+                .map_err(|err| format!("could not read stdin: {}", err))?;
+            Ok(buf)
+        }
+        Some(path) => {
+            fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path, err))
+        }
+    }
+}
+
+// Plays a day's generations in the terminal, or writes them as frames to the
+// output directory (default `frames`).
+fn record(args: &[String]) -> Result<(), String> {
+    let day = parse_day(args.first())?;
+    let format = args.get(1).map_or("play", String::as_str);
+    let format: Option<Format> = match format {
+        "play" => None,
+        format => Some(format.parse()?),
+    };
+    let input = read_input(args.get(2))?;
+    let recorder = runner::record(day, &input).map_err(|err| err.to_string())?;
+    match format {
+        None => recorder
+            .play(io::stdout(), Duration::from_millis(200))
+            .map_err(|err| format!("could not write to stdout: {}", err)),
+        Some(format) => {
+            let dir = Path::new(args.get(3).map_or("frames", String::as_str));
+            let paths = recorder
+                .write_frames(dir, format)
+                .map_err(|err| err.to_string())?;
+            println!("wrote {} frames to {}", paths.len(), dir.display());
+            Ok(())
+        }
+    }
+}
+
+// Checks every account's inputs under the data directory (default `data`).
+fn check(args: &[String]) -> Result<(), String> {
+    let data = Path::new(args.first().map_or("data", String::as_str));
+    let accounts = answers::discover(data).map_err(|err| err.to_string())?;
+    let mut failures = 0;
+    for account in &accounts {
+        for puzzle in &account.puzzles {
+            match puzzle.check() {
+                Ok(mismatches) if mismatches.is_empty() => {
+                    let status = if puzzle.part1.is_none() && puzzle.part2.is_none() {
+                        "no expected answers"
+                    } else {
+                        "ok"
+                    };
+                    println!("{}: day {:02}: {}", account.name, puzzle.day, status);
+                }
+                Ok(mismatches) => {
+                    failures += 1;
+                    for mismatch in mismatches {
+                        println!("{}: {}", account.name, mismatch);
+                    }
+                }
+                Err(err) => {
+                    failures += 1;
+                    println!("{}: day {:02}: {}", account.name, puzzle.day, err);
+                }
+            }
+        }
+    }
+    match failures {
+        0 => Ok(()),
+        n => Err(format!("{} puzzle(s) failed", n)),
+    }
+}
+
+// Steps through a console program (boot code or assembly) with commands read
+// from stdin.
+fn debug(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or("missing program path")?;
+    let source = read_input(Some(path))?;
+    let program = day08::assemble(&source).map_err(|err| err.to_string())?;
+    let mut debugger = Debugger::new(Interpreter::new(program));
+    let stdin = io::stdin();
+    debugger
+        .repl(stdin.lock(), io::stdout())
+        .map_err(|err| format!("could not run debugger: {}", err))
+}
+
+// Prints what static analysis finds in a console program, or its control-flow
+// graph as DOT.
+fn analyze(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or("missing program path")?;
+    let source = read_input(Some(path))?;
+    let program = day08::assemble(&source).map_err(|err| err.to_string())?;
+    let analysis = Analysis::new(&program);
+    match args.get(1).map(String::as_str) {
+        None => print!("{}", analysis),
+        Some("dot") => print!("{}", analysis.dot()),
+        Some(format) => return Err(format!("invalid format: {} (expected dot)", format)),
+    }
+    Ok(())
 }
@@ -0,0 +1,341 @@
+//! Recording the generations of an automaton, to play back in a terminal or
+//! write out as text or image files.
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use crate::{error::Error, grid::Grid};
+
+/// One 2-D slice of a generation, one character per cell.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Layer {
+    /// Which slice this is, e.g. `z=0, w=1`; empty if there is only one.
+    pub label: String,
+    pub grid: Grid<char>,
+}
+
+/// Something that can be drawn as one or more layers.
+pub trait Draw {
+    fn draw(&self) -> Vec<Layer>;
+}
+impl<T: fmt::Display> Draw for Grid<T> {
+    /// A single layer with the first character of each cell's `Display`.
+    fn draw(&self) -> Vec<Layer> {
+        let grid = self.map(|cell| cell.to_string().chars().next().unwrap_or(' '));
+        vec![Layer {
+            label: String::new(),
+            grid,
+        }]
+    }
+}
+
+impl Draw for Vec<Layer> {
+    fn draw(&self) -> Vec<Layer> {
+        self.clone()
+    }
+}
+
+/// A recorded generation.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Frame {
+    pub generation: usize,
+    pub layers: Vec<Layer>,
+}
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Generation {}:", self.generation)?;
+        for layer in &self.layers {
+            writeln!(f)?;
+            if !layer.label.is_empty() {
+                writeln!(f, "{}", layer.label)?;
+            }
+            write!(f, "{}", layer.grid)?;
+        }
+        Ok(())
+    }
+}
+
+/// A file format for [`Recorder::write_frames`].
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Format {
+    /// The frame as text.
+    Text,
+    /// A binary PPM image.
+    Ppm,
+    /// A PNG image.
+    Png,
+}
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Text => "txt",
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+        }
+    }
+}
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "txt" => Ok(Format::Text),
+            "ppm" => Ok(Format::Ppm),
+            "png" => Ok(Format::Png),
+            _ => Err(format!("invalid format: {} (expected txt, ppm or png)", s)),
+        }
+    }
+}
+
+/// The color of a cell drawn as `c`: dark for `#`, light for `.` and
+/// blanks, and mid grey for anything else.
+pub fn default_palette(c: char) -> [u8; 3] {
+    match c {
+        '#' => [0x20, 0x20, 0x20],
+        '.' | ' ' => [0xf0, 0xf0, 0xf0],
+        _ => [0x90, 0x90, 0x90],
+    }
+}
+
+/// Captures generations as [`Frame`]s.
+pub struct Recorder {
+    pub frames: Vec<Frame>,
+    /// Maps each cell's character to a color in images.
+    pub palette: fn(char) -> [u8; 3],
+    /// The side of each cell in image pixels.
+    pub scale: usize,
+}
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder {
+            frames: Vec::new(),
+            palette: default_palette,
+            scale: 4,
+        }
+    }
+}
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+    /// Adds `generation` as the next frame.
+    pub fn record<D: Draw + ?Sized>(&mut self, generation: &D) {
+        self.frames.push(Frame {
+            generation: self.frames.len(),
+            layers: generation.draw(),
+        });
+    }
+
+    /// Every frame as text, separated by blank lines.
+    pub fn text(&self) -> String {
+        self.frames
+            .iter()
+            .map(Frame::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// Plays the frames as an animation, clearing the terminal between them.
+    pub fn play<W: Write>(&self, mut out: W, delay: Duration) -> io::Result<()> {
+        for frame in &self.frames {
+            write!(out, "\x1b[2J\x1b[H{}", frame)?;
+            out.flush()?;
+            thread::sleep(delay);
+        }
+        Ok(())
+    }
+    /// Writes each frame to `dir/frameNNNN.<ext>`, creating `dir` if needed.
+    pub fn write_frames(&self, dir: &Path, format: Format) -> Result<Vec<PathBuf>, Error> {
+        let io_error = |path: &Path| {
+            let path = path.to_owned();
+            move |err: io::Error| Error::Write {
+                path,
+                message: err.to_string(),
+            }
+        };
+        fs::create_dir_all(dir).map_err(io_error(dir))?;
+        let mut paths = Vec::with_capacity(self.frames.len());
+        for frame in &self.frames {
+            let name = format!("frame{:04}.{}", frame.generation, format.extension());
+            let path = dir.join(name);
+            let bytes = match format {
+                Format::Text => frame.to_string().into_bytes(),
+                Format::Ppm => self.ppm(frame),
+                Format::Png => self.png(frame),
+            };
+            fs::write(&path, bytes).map_err(io_error(&path))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// `frame` as a binary PPM image.
+    pub fn ppm(&self, frame: &Frame) -> Vec<u8> {
+        let image = self.raster(frame);
+        let mut out = format!("P6\n{} {}\n255\n", image.width, image.height).into_bytes();
+        out.extend(image.items.iter().flatten());
+        out
+    }
+    /// `frame` as a PNG image.
+    pub fn png(&self, frame: &Frame) -> Vec<u8> {
+        let image = self.raster(frame);
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(image.width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(image.height as u32).to_be_bytes());
+        // 8-bit RGB, default compression and filtering, no interlacing.
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+        // Each scanline starts with filter type 0 (none).
+        let mut scanlines = Vec::with_capacity(image.height * (1 + 3 * image.width));
+        for row in image.rows() {
+            scanlines.push(0);
+            scanlines.extend(row.iter().flatten());
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut out, b"IHDR", &ihdr);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    // The frame's layers side by side, a blank cell apart, in RGB pixels.
+    fn raster(&self, frame: &Frame) -> Grid<[u8; 3]> {
+        let width: usize = frame.layers.iter().map(|l| l.grid.width + 1).sum();
+        let width = width.saturating_sub(1);
+        let height = frame
+            .layers
+            .iter()
+            .map(|l| l.grid.height)
+            .max()
+            .unwrap_or(0);
+        let mut cells = Grid::new(width, height, vec![' '; width * height]);
+        let mut left = 0;
+        for layer in &frame.layers {
+            for ((i, j), &c) in layer.grid.iter() {
+                cells.set((i, left + j), c);
+            }
+            left += layer.grid.width as i32 + 1;
+        }
+        let scale = self.scale.max(1) as i32;
+        Grid::from_fn(width * scale as usize, height * scale as usize, |(i, j)| {
+            (self.palette)(*cells.get((i / scale, j / scale)).unwrap())
+        })
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// A zlib stream holding `data` in uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 0xffff;
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        out.push(last as u8);
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = data.iter().fold((1, 0), |(a, b), &x| {
+        let a = (a + x as u32) % MOD;
+        (a, (b + a) % MOD)
+    });
+    b << 16 | a
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &x| {
+        (0..8).fold(crc ^ x as u32, |crc, _| {
+            if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{adler32, crc32, zlib_stored, Layer, Recorder};
+    use crate::grid::Grid;
+
+    fn recorder() -> Recorder {
+        let mut recorder = Recorder::new();
+        recorder.scale = 1;
+        recorder.record(&Grid::new(2, 1, vec!['#', '.']));
+        recorder.record(&vec![
+            Layer {
+                label: "z=0".to_owned(),
+                grid: Grid::new(1, 2, vec!['#', '#']),
+            },
+            Layer {
+                label: "z=1".to_owned(),
+                grid: Grid::new(1, 1, vec!['L']),
+            },
+        ]);
+        recorder
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            recorder().text(),
+            "Generation 0:\n\n#.\n\nGeneration 1:\n\nz=0\n#\n#\n\nz=1\nL\n"
+        );
+    }
+
+    #[test]
+    fn images() {
+        let recorder = recorder();
+        let frame = &recorder.frames[1];
+        let ppm = recorder.ppm(frame);
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+        assert_eq!(ppm.len(), 11 + 3 * 3 * 2);
+        // Top row: `#`, the gap, then `L`.
+        assert_eq!(
+            &ppm[11..20],
+            &[0x20, 0x20, 0x20, 0xf0, 0xf0, 0xf0, 0x90, 0x90, 0x90]
+        );
+
+        let png = recorder.png(frame);
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x03\0\0\0\x02"));
+        assert!(png.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        let data = vec![7; 70_000];
+        let zlib = zlib_stored(&data);
+        // Two blocks, each with a 5-byte header, between the zlib header and
+        // checksum.
+        assert_eq!(zlib.len(), 2 + 5 + 65_535 + 5 + 4_465 + 4);
+        assert_eq!(zlib[2], 0);
+        assert_eq!(zlib[2 + 5 + 65_535], 1);
+    }
+}
@@ -0,0 +1,181 @@
+//! Runs any day's [`Solution`] by number, timing each stage.
+
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+    error::Error, recorder::Recorder, solution::Solution,
+};
+
+/// Which part(s) of a puzzle to run.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+impl Part {
+    fn includes(self, n: u8) -> bool {
+        match self {
+            Part::One => n == 1,
+            Part::Two => n == 2,
+            Part::Both => true,
+        }
+    }
+}
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Part::One),
+            "2" => Ok(Part::Two),
+            "both" => Ok(Part::Both),
+            _ => Err(format!("invalid part: {} (expected 1, 2 or both)", s)),
+        }
+    }
+}
+
+/// The answer to one part; `None` if the input has no answer.
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub part: u8,
+    pub value: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// Everything produced by a single [`run`].
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub parse_elapsed: Duration,
+    pub answers: Vec<Answer>,
+}
+
+/// Parses `input` with the given day's [`Solution`] and answers the requested
+/// part(s).
+pub fn run(day: u32, part: Part, input: &str) -> Result<Report, Error> {
+    match day {
+        1 => run_solution::<day01::Day01>(part, input),
+        2 => run_solution::<day02::Day02>(part, input),
+        3 => run_solution::<day03::Day03>(part, input),
+        4 => run_solution::<day04::Day04>(part, input),
+        5 => run_solution::<day05::Day05>(part, input),
+        6 => run_solution::<day06::Day06>(part, input),
+        7 => run_solution::<day07::Day07>(part, input),
+        8 => run_solution::<day08::Day08>(part, input),
+        9 => run_solution::<day09::Day09>(part, input),
+        10 => run_solution::<day10::Day10>(part, input),
+        11 => run_solution::<day11::Day11>(part, input),
+        12 => run_solution::<day12::Day12>(part, input),
+        13 => run_solution::<day13::Day13>(part, input),
+        14 => run_solution::<day14::Day14>(part, input),
+        15 => run_solution::<day15::Day15>(part, input),
+        16 => run_solution::<day16::Day16>(part, input),
+        17 => run_solution::<day17::Day17>(part, input),
+        18 => run_solution::<day18::Day18>(part, input),
+        19 => run_solution::<day19::Day19>(part, input),
+        20 => run_solution::<day20::Day20>(part, input),
+        21 => run_solution::<day21::Day21>(part, input),
+        22 => run_solution::<day22::Day22>(part, input),
+        23 => run_solution::<day23::Day23>(part, input),
+        24 => run_solution::<day24::Day24>(part, input),
+        25 => run_solution::<day25::Day25>(part, input),
+        _ => Err(Error::UnknownDay(day)),
+    }
+}
+
+/// Records the generations of the given day's automaton under its part 1
+/// rules: day 11's seating until it repeats, or day 17's six boot cycles.
+pub fn record(day: u32, input: &str) -> Result<Recorder, Error> {
+    let mut recorder = Recorder::new();
+    match day {
+        11 => {
+            let rule = day11::RULE.parse().unwrap();
+            day11::record(
+                day11::parse(input)?,
+                day11::Neighborhood::Adjacent,
+                rule,
+                &mut recorder,
+            );
+        }
+        17 => {
+            let mut universe =
+                day17::Universe::<day17::P3>::from_slice(&day17::parse_slice(input)?);
+            recorder.record(&universe);
+            for _ in 0..6 {
+                universe.step();
+                recorder.record(&universe);
+            }
+        }
+        24 => {
+            let rule = day24::RULE.parse().unwrap();
+            day24::record(&day24::parse_paths(input)?, rule, 100, &mut recorder);
+        }
+        _ => return Err(Error::NoRecording(day)),
+    }
+    Ok(recorder)
+}
+
+fn run_solution<S: Solution>(part: Part, input: &str) -> Result<Report, Error> {
+    let start = Instant::now();
+    let parsed = S::parse(input)?;
+    let parse_elapsed = start.elapsed();
+
+    let mut answers = Vec::new();
+    if part.includes(1) {
+        let start = Instant::now();
+        let value = S::part1(&parsed).map(|v| v.to_string());
+        answers.push(Answer {
+            part: 1,
+            value,
+            elapsed: start.elapsed(),
+        });
+    }
+    if part.includes(2) {
+        let start = Instant::now();
+        let value = S::part2(&parsed).map(|v| v.to_string());
+        answers.push(Answer {
+            part: 2,
+            value,
+            elapsed: start.elapsed(),
+        });
+    }
+    Ok(Report {
+        parse_elapsed,
+        answers,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, Part};
+    use crate::error::Error;
+
+    #[test]
+    fn part_parser() {
+        assert_eq!("1".parse::<Part>().unwrap(), Part::One);
+        assert_eq!("2".parse::<Part>().unwrap(), Part::Two);
+        assert_eq!("both".parse::<Part>().unwrap(), Part::Both);
+        assert!("3".parse::<Part>().is_err());
+    }
+
+    #[test]
+    fn small() {
+        let input = "1721 979 366 299 675 1456";
+        let report = run(1, Part::Both, input).unwrap();
+        let values: Vec<Option<String>> = report.answers.into_iter().map(|a| a.value).collect();
+        assert_eq!(
+            values,
+            vec![Some("514579".to_owned()), Some("241861950".to_owned())]
+        );
+        assert_eq!(run(1, Part::Two, input).unwrap().answers.len(), 1);
+        assert_eq!(
+            run(26, Part::Both, input).unwrap_err(),
+            Error::UnknownDay(26)
+        );
+    }
+}
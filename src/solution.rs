@@ -0,0 +1,18 @@
+use std::fmt;
+
+use crate::error::Error;
+
+/// A single day's puzzle: raw text is parsed once into `Input`, and both
+/// parts are answered from the parsed form.
+///
+/// A part returns `None` when the input has no answer (or, for day 25, when
+/// there is no second puzzle at all).
+pub trait Solution {
+    type Input;
+    type Part1: fmt::Display;
+    type Part2: fmt::Display;
+
+    fn parse(input: &str) -> Result<Self::Input, Error>;
+    fn part1(input: &Self::Input) -> Option<Self::Part1>;
+    fn part2(input: &Self::Input) -> Option<Self::Part2>;
+}
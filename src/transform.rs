@@ -0,0 +1,190 @@
+//! The 8 symmetries of a rectangle (the dihedral group of order 8), for
+//! re-orienting grids of pixels without copying them.
+
+/// An optional left-right mirror followed by `0..4` clockwise quarter turns.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash, PartialOrd, Ord)]
+pub struct Transform {
+    pub flipped: bool,
+    pub quarter_turns: u8,
+}
+
+/// One side of a grid. Edges read left to right (top and bottom) or top to
+/// bottom (left and right).
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Transform {
+    /// Leaves everything where it is.
+    pub const IDENTITY: Transform = Transform {
+        flipped: false,
+        quarter_turns: 0,
+    };
+    /// A single clockwise quarter turn.
+    pub const ROTATE: Transform = Transform {
+        flipped: false,
+        quarter_turns: 1,
+    };
+    /// A left-right mirror.
+    pub const FLIP: Transform = Transform {
+        flipped: true,
+        quarter_turns: 0,
+    };
+
+    /// All 8 transforms, starting with the identity.
+    pub fn all() -> impl Iterator<Item = Transform> {
+        [false, true].iter().flat_map(|&flipped| {
+            (0..4).map(move |quarter_turns| Transform {
+                flipped,
+                quarter_turns,
+            })
+        })
+    }
+
+    /// `self` followed by `next`.
+    pub fn then(self, next: Transform) -> Transform {
+        // Mirroring reverses the direction of any earlier turns.
+        let turns = if next.flipped {
+            4 - self.quarter_turns
+        } else {
+            self.quarter_turns
+        };
+        Transform {
+            flipped: self.flipped != next.flipped,
+            quarter_turns: (turns + next.quarter_turns) % 4,
+        }
+    }
+
+    /// The transform that undoes `self`.
+    pub fn inverse(self) -> Transform {
+        Transform {
+            flipped: self.flipped,
+            quarter_turns: if self.flipped {
+                self.quarter_turns
+            } else {
+                (4 - self.quarter_turns) % 4
+            },
+        }
+    }
+
+    /// The `(height, width)` of a `(height, width)` grid after transforming.
+    pub fn dims(self, (height, width): (usize, usize)) -> (usize, usize) {
+        if self.quarter_turns % 2 == 1 {
+            (width, height)
+        } else {
+            (height, width)
+        }
+    }
+
+    /// The `(row, col)` in a `(height, width)` grid that ends up at
+    /// `(row, col)` once the grid is transformed.
+    pub fn source(
+        self,
+        (mut row, mut col): (usize, usize),
+        (height, width): (usize, usize),
+    ) -> (usize, usize) {
+        // Undo the turns one at a time, last first.
+        for turn in (0..self.quarter_turns).rev() {
+            let turned_height = if turn % 2 == 0 { height } else { width };
+            let prev = (turned_height - 1 - col, row);
+            row = prev.0;
+            col = prev.1;
+        }
+        if self.flipped {
+            col = width - 1 - col;
+        }
+        (row, col)
+    }
+
+    /// A transformed copy of `rows`, which must all have the same length.
+    pub fn apply<T: Clone>(self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        let dims = (rows.len(), rows.first().map_or(0, Vec::len));
+        let (height, width) = self.dims(dims);
+        (0..height)
+            .map(|r| {
+                (0..width)
+                    .map(|c| {
+                        let (sr, sc) = self.source((r, c), dims);
+                        rows[sr][sc].clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The `(row, col)` positions along `side` of a `(height, width)` grid
+    /// once transformed, in the original grid's coordinates.
+    pub fn edge(self, side: Side, dims: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let (height, width) = self.dims(dims);
+        let len = match side {
+            Side::Top | Side::Bottom => width,
+            Side::Left | Side::Right => height,
+        };
+        (0..len).map(move |i| {
+            let at = match side {
+                Side::Top => (0, i),
+                Side::Bottom => (height - 1, i),
+                Side::Left => (i, 0),
+                Side::Right => (i, width - 1),
+            };
+            self.source(at, dims)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Side, Transform};
+
+    fn grid() -> Vec<Vec<u8>> {
+        vec![vec![1, 2, 3], vec![4, 5, 6]]
+    }
+
+    #[test]
+    fn apply() {
+        assert_eq!(Transform::IDENTITY.apply(&grid()), grid());
+        assert_eq!(
+            Transform::ROTATE.apply(&grid()),
+            vec![vec![4, 1], vec![5, 2], vec![6, 3]]
+        );
+        assert_eq!(
+            Transform::FLIP.apply(&grid()),
+            vec![vec![3, 2, 1], vec![6, 5, 4]]
+        );
+        let mut all: Vec<Vec<Vec<u8>>> = Transform::all().map(|t| t.apply(&grid())).collect();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 8);
+    }
+
+    #[test]
+    fn compose() {
+        for a in Transform::all() {
+            assert_eq!(a.then(a.inverse()), Transform::IDENTITY);
+            assert_eq!(a.inverse().then(a), Transform::IDENTITY);
+            for b in Transform::all() {
+                assert_eq!(b.apply(&a.apply(&grid())), a.then(b).apply(&grid()));
+            }
+        }
+    }
+
+    #[test]
+    fn edges() {
+        let dims = (2, 3);
+        for t in Transform::all() {
+            let turned = t.apply(&grid());
+            let read =
+                |side| -> Vec<u8> { t.edge(side, dims).map(|(r, c)| grid()[r][c]).collect() };
+            assert_eq!(read(Side::Top), turned[0]);
+            assert_eq!(read(Side::Bottom), turned[turned.len() - 1]);
+            let left: Vec<u8> = turned.iter().map(|row| row[0]).collect();
+            let right: Vec<u8> = turned.iter().map(|row| row[row.len() - 1]).collect();
+            assert_eq!(read(Side::Left), left);
+            assert_eq!(read(Side::Right), right);
+        }
+    }
+}
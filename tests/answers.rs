@@ -0,0 +1,33 @@
+//! Checks every account's puzzle inputs under `data/` against the answers in
+//! its `answers.toml`.
+
+use std::{path::Path, thread};
+
+use advent_of_code_2020::answers;
+
+#[test]
+fn answers() {
+    let accounts = answers::discover(Path::new("data")).unwrap();
+    assert!(!accounts.is_empty());
+    let failures: Vec<String> = thread::scope(|s| {
+        let handles: Vec<_> = accounts
+            .iter()
+            .flat_map(|account| {
+                account.puzzles.iter().map(move |puzzle| {
+                    s.spawn(move || match puzzle.check() {
+                        Ok(mismatches) => mismatches
+                            .iter()
+                            .map(|m| format!("{}: {}", account.name, m))
+                            .collect(),
+                        Err(err) => vec![format!("{}: {}", account.name, err)],
+                    })
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}